@@ -0,0 +1,109 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use crate::config::ModulesConfig;
+use crate::modules::Module;
+use crate::value::Fields;
+
+/// Collected entries of one module as stored under `$XDG_CACHE_HOME/termgreet`
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    /// Unix time in seconds when the entries were collected
+    stored: u64,
+    /// Hash of the module's `cache_config` at that time
+    #[serde(default)]
+    config: u64,
+    entries: Vec<Fields>,
+}
+
+fn cache_dir() -> Option<PathBuf> {
+    // dirs honours $XDG_CACHE_HOME and falls back to ~/.cache
+    Some(dirs::cache_dir()?.join("termgreet"))
+}
+
+fn cache_file(module: &dyn Module) -> Option<PathBuf> {
+    Some(cache_dir()?.join(format!("{}.json", module.name())))
+}
+
+fn unix_time(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or(0)
+}
+
+fn expand_home(path: &Path) -> Option<PathBuf> {
+    match path.strip_prefix("~") {
+        Ok(rest) => Some(dirs::home_dir()?.join(rest)),
+        Err(_) => Some(path.to_path_buf()),
+    }
+}
+
+// True if any of the module's invalidation inputs changed after `stored`;
+// inputs that don't exist on this system are ignored
+fn inputs_changed(module: &dyn Module, stored: u64) -> bool {
+    module.cache_inputs().into_iter().any(|input| {
+        expand_home(&input)
+            .and_then(|path| fs::metadata(path).ok())
+            .and_then(|metadata| metadata.modified().ok())
+            .is_some_and(|modified| unix_time(modified) >= stored)
+    })
+}
+
+// FNV-1a, which unlike `DefaultHasher` stays the same across builds
+fn config_hash(module: &dyn Module, config: &ModulesConfig) -> u64 {
+    module.cache_config(config).bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Cached entries for a module, if younger than `ttl`, collected with the same
+/// settings and none of its inputs changed since
+pub fn load(module: &dyn Module, ttl: Duration, config: &ModulesConfig) -> Option<Vec<Fields>> {
+    let content = fs::read_to_string(cache_file(module)?).ok()?;
+    let entry: CacheEntry = serde_json::from_str(&content).ok()?;
+
+    let age = unix_time(SystemTime::now()).saturating_sub(entry.stored);
+    if age >= ttl.as_secs() || entry.config != config_hash(module, config) || inputs_changed(module, entry.stored) {
+        return None;
+    }
+
+    Some(entry.entries)
+}
+
+/// Store freshly collected entries. Failures are ignored; the cache is only an optimization.
+pub fn store(module: &dyn Module, entries: &[Fields], config: &ModulesConfig) {
+    let (Some(dir), Some(file)) = (cache_dir(), cache_file(module)) else {
+        return;
+    };
+    let entry = CacheEntry {
+        stored: unix_time(SystemTime::now()),
+        config: config_hash(module, config),
+        entries: entries.to_vec(),
+    };
+    let Ok(content) = serde_json::to_string(&entry) else {
+        return;
+    };
+    let _ = write_atomically(&dir, &file, &content);
+}
+
+/// Path of a file of our own in the cache directory
+pub fn path(name: &str) -> Option<PathBuf> {
+    Some(cache_dir()?.join(name))
+}
+
+/// Replace a file in the cache directory, creating the directory if needed
+pub fn write(name: &str, content: &str) -> io::Result<()> {
+    let dir = cache_dir().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no cache directory"))?;
+    write_atomically(&dir, &dir.join(name), content)
+}
+
+// Write to a temporary file first so concurrent shells never read half a file
+fn write_atomically(dir: &Path, file: &Path, content: &str) -> io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let name = file.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    let temp = dir.join(format!(".{}.{}.tmp", name, std::process::id()));
+    fs::write(&temp, content)?;
+    fs::rename(&temp, file).inspect_err(|_| {
+        let _ = fs::remove_file(&temp);
+    })
+}
@@ -0,0 +1,320 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
+
+thread_local! {
+    // Deadline for external commands started by the collector on this thread
+    static COMMAND_DEADLINE: Cell<Option<Instant>> = const { Cell::new(None) };
+    // Set when a command was killed or skipped because the deadline passed
+    static DEADLINE_HIT: Cell<bool> = const { Cell::new(false) };
+}
+
+static RUNNER: OnceLock<Box<dyn CommandRunner>> = OnceLock::new();
+
+// Children still running, with the thread waiting on them, so the commands of a
+// collector that was given up on don't outlive it
+static RUNNING: Mutex<Vec<(ThreadId, Arc<Mutex<Child>>)>> = Mutex::new(Vec::new());
+
+/// Runs the external tools collectors depend on (`lspci`, `xrandr`, `nvidia-smi`, ...)
+pub trait CommandRunner: Send + Sync {
+    /// Stdout of a successful run; `None` if the command is missing, failed or was killed
+    fn run(&self, command: &str, args: &[&str], env: &[(&str, &str)]) -> Option<String>;
+
+    /// Body of an HTTP GET request over IPv4 or IPv6; `get` performs it on this machine
+    fn http_get(&self, url: &str, ipv6: bool, get: &dyn Fn() -> Option<String>) -> Option<String> {
+        let _ = (url, ipv6);
+        get()
+    }
+
+    /// False if output doesn't come from this machine right now
+    fn is_live(&self) -> bool {
+        true
+    }
+}
+
+/// Use `runner` instead of running commands directly for the rest of the run.
+/// Only the first call has an effect.
+pub fn set_runner(runner: Box<dyn CommandRunner>) {
+    let _ = RUNNER.set(runner);
+}
+
+pub fn run(command: &str, args: &[&str], env: &[(&str, &str)]) -> Option<String> {
+    match RUNNER.get() {
+        Some(runner) => runner.run(command, args, env),
+        None => LiveRunner.run(command, args, env),
+    }
+}
+
+/// Fetch `url` through the runner, so HTTP lookups are recorded and replayed
+/// like commands; `get` performs the request when running live
+pub fn http_get(url: &str, ipv6: bool, get: &dyn Fn() -> Option<String>) -> Option<String> {
+    match RUNNER.get() {
+        Some(runner) => runner.http_get(url, ipv6, get),
+        None => get(),
+    }
+}
+
+/// True unless commands are being recorded or replayed
+pub fn is_live() -> bool {
+    RUNNER.get().is_none_or(|runner| runner.is_live())
+}
+
+/// Run a collector with a deadline for the commands it starts; also reports
+/// whether the deadline cut anything short
+pub fn with_deadline<T>(deadline: Instant, collect: impl FnOnce() -> T) -> (T, bool) {
+    COMMAND_DEADLINE.set(Some(deadline));
+    DEADLINE_HIT.set(false);
+    let result = collect();
+    COMMAND_DEADLINE.set(None);
+    (result, DEADLINE_HIT.get())
+}
+
+/// Time left before the current collector's deadline, for work that isn't an
+/// external command (such as HTTP requests); `None` outside a collector
+pub fn remaining() -> Option<Duration> {
+    COMMAND_DEADLINE.get().map(|deadline| deadline.saturating_duration_since(Instant::now()))
+}
+
+/// Kill the commands still running for the collector on `thread`, once its
+/// results are no longer waited for
+pub fn kill_started_by(thread: ThreadId) {
+    for (_, child) in lock_running().iter().filter(|(owner, _)| *owner == thread) {
+        let mut child = child.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+/// Runs commands on this machine, killing them once the collector's deadline passes
+pub struct LiveRunner;
+
+impl CommandRunner for LiveRunner {
+    fn run(&self, command: &str, args: &[&str], env: &[(&str, &str)]) -> Option<String> {
+        let mut cmd = Command::new(command);
+        cmd.args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null());
+
+        for (key, value) in env {
+            cmd.env(key, value);
+        }
+
+        output_with_deadline(cmd)
+    }
+}
+
+// Like `Command::output`, but kills the child once the collector's deadline passes.
+// Returns stdout of a successful run.
+fn output_with_deadline(mut cmd: Command) -> Option<String> {
+    let deadline = COMMAND_DEADLINE.get();
+    let expired = || deadline.is_some_and(|deadline| Instant::now() >= deadline);
+
+    // Later fallbacks of a collector that already ran out of time are skipped
+    if expired() {
+        DEADLINE_HIT.set(true);
+        return None;
+    }
+
+    let mut child = cmd.spawn().ok()?;
+
+    // Drain stdout on its own thread so a full pipe can't stall the child
+    let mut stdout = child.stdout.take()?;
+    let reader = thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = stdout.read_to_end(&mut buffer);
+        buffer
+    });
+
+    let child = Arc::new(Mutex::new(child));
+    let thread = thread::current().id();
+    lock_running().push((thread, Arc::clone(&child)));
+    let status = wait_with_deadline(&child, expired);
+    lock_running().retain(|(_, running)| !Arc::ptr_eq(running, &child));
+    let status = status?;
+
+    let output = reader.join().ok()?;
+    if status.success() {
+        Some(String::from_utf8_lossy(&output).to_string())
+    } else {
+        None
+    }
+}
+
+// Poll the child until it exits; `None` if it was killed at the deadline
+fn wait_with_deadline(child: &Mutex<Child>, expired: impl Fn() -> bool) -> Option<ExitStatus> {
+    loop {
+        let mut child = child.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(status) = child.try_wait().ok()? {
+            return Some(status);
+        }
+        if expired() {
+            let _ = child.kill();
+            let _ = child.wait();
+            DEADLINE_HIT.set(true);
+            return None;
+        }
+        drop(child);
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+fn lock_running() -> MutexGuard<'static, Vec<(ThreadId, Arc<Mutex<Child>>)>> {
+    RUNNING.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// One command invocation and what it printed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Invocation {
+    command: String,
+    args: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    env: BTreeMap<String, String>,
+    /// `None` if the command was missing or failed
+    stdout: Option<String>,
+}
+
+impl Invocation {
+    fn matches(&self, command: &str, args: &[&str], env: &[(&str, &str)]) -> bool {
+        self.command == command
+            && self.args.iter().map(String::as_str).eq(args.iter().copied())
+            && self.env.len() == env.len()
+            && env.iter().all(|(key, value)| self.env.get(*key).is_some_and(|recorded| recorded == value))
+    }
+}
+
+/// One HTTP request and the body it returned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Request {
+    url: String,
+    ipv6: bool,
+    /// `None` if the request failed or timed out
+    body: Option<String>,
+}
+
+/// Recorded command output and HTTP responses, stored as JSON
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Bundle {
+    commands: Vec<Invocation>,
+    // Bundles recorded before HTTP lookups were recorded have none
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    requests: Vec<Request>,
+}
+
+/// Runs commands live and writes every invocation to a bundle file
+pub struct RecordingRunner {
+    path: PathBuf,
+    bundle: Mutex<Bundle>,
+}
+
+impl RecordingRunner {
+    /// Start an empty bundle at `path`, failing early if it can't be written
+    pub fn create(path: &Path) -> Result<Self> {
+        let runner = Self {
+            path: path.to_path_buf(),
+            bundle: Mutex::new(Bundle::default()),
+        };
+        runner
+            .save(&Bundle::default())
+            .with_context(|| format!("Failed to write command bundle: {}", path.display()))?;
+        Ok(runner)
+    }
+
+    fn save(&self, bundle: &Bundle) -> Result<()> {
+        let content = serde_json::to_string_pretty(bundle)?;
+        fs::write(&self.path, content + "\n")?;
+        Ok(())
+    }
+}
+
+impl CommandRunner for RecordingRunner {
+    fn run(&self, command: &str, args: &[&str], env: &[(&str, &str)]) -> Option<String> {
+        let stdout = LiveRunner.run(command, args, env);
+
+        // Commands killed by the deadline didn't really fail; replaying them would
+        // turn a slow tool into a missing one
+        if DEADLINE_HIT.get() {
+            return stdout;
+        }
+
+        let invocation = Invocation {
+            command: command.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            env: env.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect(),
+            stdout: stdout.clone(),
+        };
+        // Saved after every command, since collectors still running at the
+        // global deadline are abandoned rather than joined
+        if let Ok(mut bundle) = self.bundle.lock() {
+            bundle.commands.push(invocation);
+            let _ = self.save(&bundle);
+        }
+
+        stdout
+    }
+
+    fn http_get(&self, url: &str, ipv6: bool, get: &dyn Fn() -> Option<String>) -> Option<String> {
+        let body = get();
+        let request = Request {
+            url: url.to_string(),
+            ipv6,
+            body: body.clone(),
+        };
+        if let Ok(mut bundle) = self.bundle.lock() {
+            bundle.requests.push(request);
+            let _ = self.save(&bundle);
+        }
+        body
+    }
+
+    fn is_live(&self) -> bool {
+        false
+    }
+}
+
+/// Answers commands and HTTP requests from a recorded bundle instead of running
+/// anything. Commands missing from the bundle behave as if they weren't installed,
+/// requests missing from it as if the service were unreachable.
+pub struct ReplayRunner {
+    bundle: Bundle,
+}
+
+impl ReplayRunner {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read command bundle: {}", path.display()))?;
+        let bundle = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse command bundle: {}", path.display()))?;
+        Ok(Self { bundle })
+    }
+}
+
+impl CommandRunner for ReplayRunner {
+    fn run(&self, command: &str, args: &[&str], env: &[(&str, &str)]) -> Option<String> {
+        self.bundle
+            .commands
+            .iter()
+            .find(|invocation| invocation.matches(command, args, env))
+            .and_then(|invocation| invocation.stdout.clone())
+    }
+
+    fn http_get(&self, url: &str, ipv6: bool, _get: &dyn Fn() -> Option<String>) -> Option<String> {
+        self.bundle
+            .requests
+            .iter()
+            .find(|request| request.url == url && request.ipv6 == ipv6)
+            .and_then(|request| request.body.clone())
+    }
+
+    fn is_live(&self) -> bool {
+        false
+    }
+}
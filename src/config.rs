@@ -1,7 +1,12 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+use crate::modules::{find, registry, Module};
+use crate::commands;
+use crate::sysroot;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Config {
@@ -18,6 +23,9 @@ pub struct GeneralConfig {
     pub title: Option<String>,
     pub separator: SeparatorConfig,
     pub colors: ColorsConfig,
+    /// Read /proc, /sys and /etc from this directory instead of `/`
+    #[serde(default)]
+    pub sysroot: Option<PathBuf>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -49,6 +57,9 @@ pub struct DisplayConfig {
     pub border_bottom: String,
     pub border_color: String,
     pub block_rendering: BlockRenderingConfig,
+    // Draw fast modules immediately and fill in slow ones as they finish
+    #[serde(default)]
+    pub progressive: bool,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -71,78 +82,116 @@ pub struct ImageSize {
     pub cell_height: u32, // Pixel pro Terminal-Zeichen (Höhe)
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ModulesConfig {
     pub show_versions: bool,
-    pub os: bool,
-    pub kernel: bool,
-    pub linux: bool,
-    pub uptime: bool,
-    pub os_age: bool,
-    pub packages: bool,
-    pub flatpak_packages: bool,
-    pub packages_combined: bool,
-    pub shell: bool,
-    pub resolution: bool,
-    pub network: bool,
-    pub public_ip: bool,
-    pub de: bool,
-    pub wm: bool,
-    pub theme: bool,
-    pub icons: bool,
-    pub terminal: bool,
-    pub terminal_shell_combined: bool,
-    pub font: bool,
-    pub user: bool,
-    pub hostname: bool,
-    pub user_at_host: bool,
-    pub cpu: bool,
-    pub cpu_temp: bool,
-    pub gpu: bool,
-    pub gpu_temp: bool,
-    pub temp_combined: bool,
-    pub gpu_driver: bool,
-    pub memory: bool,
-    pub disk: bool,
-    pub dysk: bool,
-    pub battery: bool,
-    pub locale: bool,
-    pub display_names: ModuleDisplayConfig,
+    // Output order: module names, "---" for a blank separator line and
+    // "--- Title" for a group header. Falls back to the built-in order when absent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<Vec<String>>,
+    // Custom display names keyed by module name; unset modules use their default label
+    #[serde(default)]
+    pub display_names: BTreeMap<String, String>,
+    // Format strings keyed by module name, e.g. memory = "{used} of {total} ({percent})"
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub format: BTreeMap<String, String>,
+    #[serde(default)]
+    pub timeouts: TimeoutsConfig,
+    #[serde(default)]
+    pub cache: CacheConfig,
+    #[serde(default)]
+    pub temperature: TemperatureConfig,
+    #[serde(default)]
+    pub public_ip_lookup: PublicIpLookupConfig,
+    #[serde(default)]
+    pub package_counts: PackageCountsConfig,
+    #[serde(default)]
+    pub update_check: UpdateCheckConfig,
+    // One `name = true/false` entry per module in the registry
+    #[serde(flatten)]
+    pub enabled: BTreeMap<String, bool>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-pub struct ModuleDisplayConfig {
-    pub user_at_host: Option<String>,
-    pub os: Option<String>,
-    pub kernel: Option<String>,
-    pub linux: Option<String>,
-    pub uptime: Option<String>,
-    pub os_age: Option<String>,
-    pub packages: Option<String>,
-    pub shell: Option<String>,
-    pub resolution: Option<String>,
-    pub network: Option<String>,
-    pub public_ip: Option<String>,
-    pub de: Option<String>,
-    pub wm: Option<String>,
-    pub theme: Option<String>,
-    pub icons: Option<String>,
-    pub terminal: Option<String>,
-    pub terminal_shell_combined: Option<String>,
-    pub font: Option<String>,
-    pub user: Option<String>,
-    pub hostname: Option<String>,
+/// Time budgets in milliseconds; modules that exceed them are shown as timed out
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TimeoutsConfig {
+    // Total time collection may take before the greeting is printed anyway
+    pub global: u64,
+    // Budget per module, including the external commands it runs
+    pub default: u64,
+    // Per-module overrides keyed by module name, e.g. public_ip = 4000
+    #[serde(flatten)]
+    pub modules: BTreeMap<String, u64>,
+}
+
+/// On-disk cache for slow modules under `$XDG_CACHE_HOME/termgreet`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    pub enabled: bool,
+    // Per-module TTL overrides in seconds keyed by module name; 0 disables caching
+    #[serde(flatten)]
+    pub ttl: BTreeMap<String, u64>,
+}
+
+/// Sensors behind the temperature modules, selected as `chip` or `chip/label`
+/// (e.g. "k10temp/Tctl", "amdgpu/junction"); unset picks the most representative one
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TemperatureConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub cpu: Option<String>,
-    pub cpu_temp: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub gpu: Option<String>,
-    pub gpu_temp: Option<String>,
-    pub temp_combined: Option<String>,
-    pub gpu_driver: Option<String>,
-    pub memory: Option<String>,
-    pub disk: Option<String>,
-    pub dysk: Option<String>,
-    pub battery: Option<String>,
-    pub locale: Option<String>,
+    // Entries of temp_combined: "cpu", "gpu" or a sensor selector
+    pub combined: Vec<String>,
+}
+
+/// Sub-counts shown next to a package manager's total
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PackageCountsConfig {
+    // In display order; pacman knows explicit, foreign and orphans, dpkg manual and auto
+    pub breakdown: Vec<PackageBreakdown>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PackageBreakdown {
+    /// Installed on purpose rather than as a dependency
+    Explicit,
+    /// Not in any sync repository (AUR, local builds)
+    Foreign,
+    /// Dependencies nothing requires any more
+    Orphans,
+    /// Marked as manually installed by apt
+    Manual,
+    /// Marked as automatically installed by apt
+    Auto,
+}
+
+/// Background check behind the updates module
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct UpdateCheckConfig {
+    // Seconds after which the greeting starts a new check
+    pub interval: u64,
+    // Start stale checks from the greeting; turn off when a timer runs `termgreet --refresh-updates`
+    pub background: bool,
+}
+
+/// Services the public_ip module asks for this machine's address
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PublicIpLookupConfig {
+    // Tried in order until one answers; plain-text replies or JSON like ipinfo.io's
+    pub endpoints: Vec<String>,
+    pub ipv4: bool,
+    pub ipv6: bool,
+    // HTTP or SOCKS proxy URL; unset uses HTTPS_PROXY / ALL_PROXY from the environment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -171,6 +220,7 @@ impl Default for Config {
                     info: "bright_white".to_string(),
                     separator: "bright_blue".to_string(),
                 },
+                sysroot: None,
             },
             display: DisplayConfig {
                 show_image: true,
@@ -198,75 +248,23 @@ impl Default for Config {
                     sampling_method: "average".to_string(),
                     enable_dithering: false,
                 },
+                progressive: false,
             },
             modules: ModulesConfig {
                 show_versions: true,
-                os: true,
-                kernel: true,
-                linux: true,
-                uptime: true,
-                os_age: true,
-                packages: false,
-                flatpak_packages: false,
-                packages_combined: true,
-                shell: true,
-                resolution: true,
-                network: true,
-                public_ip: false,
-                de: true,
-                wm: true,
-                theme: false,
-                icons: false,
-                terminal: true,
-                terminal_shell_combined: false,
-                font: true,
-                user: true,
-                hostname: true,
-                user_at_host: true,
-                cpu: true,
-                cpu_temp: true,
-                gpu: true,
-                gpu_temp: true,
-                temp_combined: false,
-                gpu_driver: true,
-                memory: true,
-                disk: true,
-                dysk: true,
-                battery: true,
-                locale: false,
-                display_names: ModuleDisplayConfig {
-                    user_at_host: None,
-                    os: None,
-                    kernel: None,
-                    linux: None,
-                    uptime: None,
-                    os_age: None,
-                    packages: None,
-                    shell: None,
-                    resolution: None,
-                    network: None,
-                    public_ip: None,
-                    de: None,
-                    wm: None,
-                    theme: None,
-                    icons: None,
-                    terminal: None,
-                    terminal_shell_combined: None,
-                    font: None,
-                    user: None,
-                    hostname: None,
-                    cpu: None,
-                    cpu_temp: None,
-                    gpu: None,
-                    gpu_temp: None,
-                    temp_combined: None,
-                    gpu_driver: None,
-                    memory: None,
-                    disk: None,
-                    dysk: None,
-                    battery: None,
-                    locale: None,
-                },
+                order: None,
+                display_names: BTreeMap::new(),
+                format: BTreeMap::new(),
+                timeouts: TimeoutsConfig::default(),
+                cache: CacheConfig::default(),
+                temperature: TemperatureConfig::default(),
+                public_ip_lookup: PublicIpLookupConfig::default(),
+                package_counts: PackageCountsConfig::default(),
+                update_check: UpdateCheckConfig::default(),
+                enabled: registry()
+                    .iter()
+                    .map(|module| (module.name().to_string(), module.enabled_by_default()))
+                    .collect(),
             },
             show_motd: true,
             motd_file: dirs::config_dir()
@@ -361,6 +359,15 @@ impl Config {
         let config: Config = toml::from_str(&content)
             .with_context(|| format!("Failed to parse config file: {}", path.display()))?;
         
+        if let Some(order) = &config.modules.order {
+            for entry in order {
+                let entry = entry.trim();
+                if !entry.starts_with("---") && find(entry).is_none() {
+                    eprintln!("Warning: Unknown module '{}' in modules.order", entry);
+                }
+            }
+        }
+        
         Ok(config)
     }
 
@@ -380,6 +387,117 @@ impl Config {
     }
 }
 
+impl Default for TimeoutsConfig {
+    fn default() -> Self {
+        Self {
+            global: 3000,
+            default: 2000,
+            modules: BTreeMap::new(),
+        }
+    }
+}
+
+impl TimeoutsConfig {
+    pub fn global_budget(&self) -> Duration {
+        Duration::from_millis(self.global)
+    }
+
+    pub fn module_budget(&self, name: &str) -> Duration {
+        Duration::from_millis(self.modules.get(name).copied().unwrap_or(self.default))
+    }
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl: BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for TemperatureConfig {
+    fn default() -> Self {
+        Self {
+            cpu: None,
+            gpu: None,
+            combined: vec!["cpu".to_string(), "gpu".to_string()],
+        }
+    }
+}
+
+impl Default for UpdateCheckConfig {
+    fn default() -> Self {
+        Self {
+            interval: 6 * 60 * 60,
+            background: true,
+        }
+    }
+}
+
+impl Default for PublicIpLookupConfig {
+    fn default() -> Self {
+        Self {
+            endpoints: vec![
+                "https://ipinfo.io/json".to_string(),
+                "https://v6.ipinfo.io/json".to_string(),
+                "https://api64.ipify.org".to_string(),
+                "https://icanhazip.com".to_string(),
+            ],
+            ipv4: true,
+            ipv6: false,
+            proxy: None,
+        }
+    }
+}
+
+impl CacheConfig {
+    /// Whether anything is cached at all. Cached entries describe the live
+    /// system, not a snapshot, and would keep commands from being recorded.
+    pub fn is_active(&self) -> bool {
+        self.enabled && !sysroot::is_set() && commands::is_live()
+    }
+
+    /// How long the module's entries may be served from cache, if at all
+    pub fn ttl(&self, module: &dyn Module) -> Option<Duration> {
+        if !self.is_active() {
+            return None;
+        }
+        match self.ttl.get(module.name()) {
+            Some(0) => None,
+            Some(seconds) => Some(Duration::from_secs(*seconds)),
+            None => module.cache_ttl(),
+        }
+    }
+}
+
+impl ModulesConfig {
+    /// Without `order`, modules missing from the config are treated as disabled.
+    /// With `order`, a listed module is enabled unless explicitly set to false.
+    pub fn is_enabled(&self, name: &str) -> bool {
+        match &self.order {
+            Some(order) => {
+                order.iter().any(|entry| entry.trim() == name) && self.enabled.get(name) != Some(&false)
+            }
+            None => self.enabled.get(name).copied().unwrap_or(false),
+        }
+    }
+
+    pub fn display_name(&self, module: &dyn Module) -> String {
+        self.display_names
+            .get(module.display_name_key())
+            .cloned()
+            .unwrap_or_else(|| module.default_label().to_string())
+    }
+
+    pub fn format<'a>(&'a self, module: &'a dyn Module) -> &'a str {
+        self.format
+            .get(module.name())
+            .map(String::as_str)
+            .unwrap_or_else(|| module.default_format())
+    }
+}
+
 impl MotdConfig {
     pub fn load(path: &Path) -> Result<Self> {
         if !path.exists() {
@@ -1,10 +1,28 @@
 use anyhow::Result;
+use std::io::{self, Write};
 use colored::{Colorize, Color, ColoredString};
 use image;
 use unicode_width::UnicodeWidthStr;
 use crate::config::{Config, MotdConfig};
-use crate::system_info::SystemInfo;
+use crate::system_info::{Collection, SystemInfo};
 use crate::kitty_graphics::KittyGraphics;
+use crate::modules::{enabled_modules, layout, Cost, LayoutEntry};
+
+// Shown for modules that are still being collected in progressive mode
+const PLACEHOLDER: &str = "…";
+
+/// Where the info lines ended up on screen, relative to the cursor after drawing
+struct Placement {
+    /// Rows from the cursor back up to the first row of the drawn block
+    block_rows: usize,
+    /// Rows from the cursor back up to the first info line
+    first_line_rows: usize,
+    /// 1-based terminal column where info lines start
+    column: usize,
+    /// The image is shown with kitty graphics, so it stays in place and only
+    /// the text beside it is redrawn
+    fixed_image: bool,
+}
 
 pub struct Display {
     config: Config,
@@ -22,6 +40,97 @@ impl Display {
     }
 
     pub fn show(&self, system_info: &SystemInfo) -> Result<()> {
+        self.show_title();
+        
+        // Prepare system info lines
+        let info_lines = self.prepare_system_info_lines(system_info);
+        
+        self.draw(&info_lines)?;
+        self.show_motd_if_enabled()?;
+        
+        Ok(())
+    }
+    
+    /// Draw the layout right away with placeholders for modules still being
+    /// collected, then fill in their lines in place as the collectors finish
+    pub fn show_progressive(&self, mut collection: Collection) -> Result<()> {
+        let modules = &self.config.modules;
+        
+        // Fast modules are awaited so only slow ones start out as placeholders
+        let awaited: Vec<&str> = enabled_modules(modules)
+            .into_iter()
+            .filter(|module| module.cost() == Cost::Fast)
+            .map(|module| module.name())
+            .collect();
+        while awaited.iter().any(|name| collection.is_pending(name)) {
+            if collection.next().is_none() {
+                break;
+            }
+        }
+        
+        self.show_title();
+        let mut drawn = self.prepare_progress_lines(&collection);
+        let mut placement = self.draw(&drawn)?;
+        io::stdout().flush()?;
+        
+        // Multi-line modules hold a single placeholder line until they finish
+        while collection.next().is_some() {
+            let lines = self.prepare_progress_lines(&collection);
+            placement = self.redraw(placement, &drawn, &lines)?;
+            io::stdout().flush()?;
+            drawn = lines;
+        }
+        
+        // Stragglers are only marked as timed out once collection ends, and
+        // empty modules are only dropped now
+        let lines = self.prepare_system_info_lines(collection.info());
+        self.redraw(placement, &drawn, &lines)?;
+        
+        self.show_motd_if_enabled()?;
+        Ok(())
+    }
+    
+    // Show the new info lines, redrawing the whole block when their count changed
+    fn redraw(&self, placement: Placement, drawn: &[String], lines: &[String]) -> Result<Placement> {
+        if lines.len() == drawn.len() {
+            self.update_lines(&placement, drawn, lines);
+            return Ok(placement);
+        }
+        if placement.fixed_image {
+            return Ok(self.redraw_kitty_text(&placement, drawn, lines));
+        }
+        print!("\x1b[{}A\x1b[1G\x1b[J", placement.block_rows);
+        self.draw(lines)
+    }
+    
+    // Rewrite the text column of the kitty layout without sending the image again,
+    // reserving more rows first if the text grew past them
+    fn redraw_kitty_text(&self, placement: &Placement, drawn: &[String], lines: &[String]) -> Placement {
+        let reserved = self.kitty_reserved_rows(lines.len());
+        for _ in placement.block_rows..reserved {
+            println!();
+        }
+        let block_rows = placement.block_rows.max(reserved);
+        if block_rows > 0 {
+            print!("\x1b[{}A", block_rows);
+        }
+        let border_rows = if self.config.display.show_border { 2 } else { 0 };
+        self.write_kitty_text(lines, reserved, drawn.len() + border_rows)
+    }
+    
+    // Rewrite the info lines that differ, leaving the cursor where it was
+    fn update_lines(&self, placement: &Placement, drawn: &[String], lines: &[String]) {
+        for (index, (old, new)) in drawn.iter().zip(lines).enumerate() {
+            if old == new {
+                continue;
+            }
+            let rows_up = placement.first_line_rows - index;
+            print!("\x1b[{}A\x1b[{}G\x1b[K{}", rows_up, placement.column, new);
+            print!("\x1b[{}B\x1b[1G", rows_up);
+        }
+    }
+    
+    fn show_title(&self) {
         // Show title if configured and enabled
         if self.config.general.show_title {
             if let Some(ref title) = self.config.general.title {
@@ -29,52 +138,39 @@ impl Display {
                 println!();
             }
         }
-        
-        // Prepare system info lines
-        let info_lines = self.prepare_system_info_lines(system_info);
-        
+    }
+    
+    // Draw the image (if any) and info lines in the configured layout
+    fn draw(&self, info_lines: &[String]) -> Result<Placement> {
         // Display PNG image if configured and available
-        if self.show_images && self.config.display.show_image {
-            let mut image_to_use: Option<std::path::PathBuf> = None;
-            
-            // First, check if a specific image path is configured
-            if let Some(ref image_path) = self.config.display.image_path {
-                if image_path.exists() {
-                    image_to_use = Some(image_path.clone());
-                }
-            }
-            
-            // If no specific image or it doesn't exist, try default logo
-            if image_to_use.is_none() {
-                if let Some(config_dir) = dirs::config_dir() {
-                    let default_logo = config_dir.join("termgreet").join("pngs").join("termgreet_logo.png");
-                    if default_logo.exists() {
-                        image_to_use = Some(default_logo);
-                    }
-                }
-            }
-            
-            // Display the image if we found one
-            if let Some(image_path) = image_to_use {
-                // Check layout configuration
-                match self.config.display.layout.as_str() {
-                    "horizontal" => {
-                        self.show_horizontal_layout(&image_path, &info_lines)?;
-                    },
-                    _ => { // "vertical" or any other value defaults to vertical
-                        self.show_image_with_info(&image_path, &info_lines)?;
-                    }
-                }
-                self.show_motd_if_enabled()?;
-                return Ok(());
-            }
+        if let Some(image_path) = self.image_to_use() {
+            // Check layout configuration
+            return match self.config.display.layout.as_str() {
+                "horizontal" => self.show_horizontal_layout(&image_path, info_lines),
+                // "vertical" or any other value defaults to vertical
+                _ => self.show_image_with_info(&image_path, info_lines),
+            };
         }
         
         // No image configured or available - show info only
-        self.show_info_only(&info_lines);
-        self.show_motd_if_enabled()?;
+        Ok(self.show_info_only(info_lines))
+    }
+    
+    fn image_to_use(&self) -> Option<std::path::PathBuf> {
+        if !self.show_images || !self.config.display.show_image {
+            return None;
+        }
         
-        Ok(())
+        // First, check if a specific image path is configured
+        if let Some(ref image_path) = self.config.display.image_path
+            && image_path.exists()
+        {
+            return Some(image_path.clone());
+        }
+        
+        // If no specific image or it doesn't exist, try default logo
+        let default_logo = dirs::config_dir()?.join("termgreet").join("pngs").join("termgreet_logo.png");
+        default_logo.exists().then_some(default_logo)
     }
 
     fn is_kitty_terminal(&self) -> bool {
@@ -260,98 +356,101 @@ impl Display {
         Ok(())
     }
     
-    fn show_image_with_info(&self, image_path: &std::path::Path, info_lines: &[String]) -> Result<()> {
+    fn show_image_with_info(&self, image_path: &std::path::Path, info_lines: &[String]) -> Result<Placement> {
         let config = &self.config.display;
         
         // Check if we should use Kitty graphics protocol
         let use_kitty = config.prefer_kitty_graphics && self.is_kitty_terminal();
         
         if use_kitty {
-            self.show_horizontal_layout(&image_path.to_path_buf(), info_lines)?;
+            self.show_horizontal_layout(image_path, info_lines)
         } else {
-            self.show_horizontal_layout_with_blocks(image_path, info_lines)?;
+            self.show_horizontal_layout_with_blocks(image_path, info_lines)
         }
-        
-        Ok(())
     }
 
-    fn show_horizontal_layout(&self, image_path: &std::path::PathBuf, info_lines: &[String]) -> Result<()> {
-        let image_width = self.config.display.image_size.width as usize;
-        let padding = self.config.display.padding as usize;
-        let border_color = Display::get_color(&self.config.display.border_color);
-        
+    fn show_horizontal_layout(&self, image_path: &std::path::Path, info_lines: &[String]) -> Result<Placement> {
         // For Kitty Graphics Protocol, we need to implement true side-by-side layout
         if self.config.display.prefer_kitty_graphics && self.is_kitty_terminal() {
-            // Calculate dimensions
             let image_height = self.config.display.image_size.height as usize;
-            let info_start_col = image_width + padding;
-            let total_content_height = std::cmp::max(image_height, info_lines.len());
-            
-            // Add border height to total if enabled
-            let border_height = if self.config.display.show_border { 4 } else { 0 }; // top border + spacing + bottom border + spacing
-            let total_space_needed = total_content_height + border_height;
+            let reserved = self.kitty_reserved_rows(info_lines.len());
             
             // Print empty lines to reserve space for all content
-            for _ in 0..total_space_needed {
+            for _ in 0..reserved {
                 println!();
             }
             
             // Move cursor back to the beginning of our reserved space
-            print!("\x1b[{}A", total_space_needed);
+            print!("\x1b[{}A", reserved);
             
             // Render the image with Kitty Graphics Protocol at current position
             self.render_image_to_terminal(image_path)?;
             
             // Move cursor back up to align with top of image for module output
             print!("\x1b[{}A", image_height);
-            print!("\x1b[{}C", info_start_col); // Move cursor right to info column
-            
-            // Render top border for module area if enabled
-            if self.config.display.show_border {
-                print!("{}", self.render_border(&self.config.display.border_top, border_color));
-                print!("\x1b[1B"); // Move cursor down one line
-                print!("\x1b[{}G", info_start_col + 1); // Move cursor to info column
-            }
-            
-            // Print each info line with proper cursor positioning
-            for (i, line) in info_lines.iter().enumerate() {
-                if i > 0 {
-                    // Move to next line and position cursor at info column
-                    print!("\x1b[1B"); // Move cursor down one line
-                    print!("\x1b[{}G", info_start_col + 1); // Move cursor to specific column (1-indexed)
-                }
-                print!("{}", line);
-            }
-            
-            // Render bottom border for module area if enabled
-            if self.config.display.show_border {
-                print!("\x1b[1B"); // Move cursor down one line
-                print!("\x1b[{}G", info_start_col + 1); // Move cursor to info column
-                print!("{}", self.render_border(&self.config.display.border_bottom, border_color));
-            }
-            
-            // Move cursor to the end of our reserved space
-            let remaining_lines = if total_space_needed > (image_height + info_lines.len() + border_height) {
-                total_space_needed - (image_height + info_lines.len() + border_height)
-            } else {
-                0
-            };
-            
-            if remaining_lines > 0 {
-                print!("\x1b[{}B", remaining_lines);
-            }
-            print!("\x1b[1G"); // Move cursor to beginning of line
-            println!(); // Add blank line before MOTD
             
+            Ok(self.write_kitty_text(info_lines, reserved, 0))
         } else {
             // Use block-based rendering for true side-by-side layout
-            self.show_horizontal_layout_with_blocks(image_path, info_lines)?;
+            self.show_horizontal_layout_with_blocks(image_path, info_lines)
         }
+    }
+    
+    // Rows the kitty layout reserves for the image and the text beside it
+    fn kitty_reserved_rows(&self, line_count: usize) -> usize {
+        let image_height = self.config.display.image_size.height as usize;
+        // top border + spacing + bottom border + spacing
+        let border_height = if self.config.display.show_border { 4 } else { 0 };
+        std::cmp::max(image_height, line_count) + border_height
+    }
+    
+    // Write the borders and info lines beside the kitty image, starting on the cursor's
+    // row, and blank the rest of the first `clear_rows` rows' text. Leaves the cursor
+    // on the row below the `reserved` ones.
+    fn write_kitty_text(&self, info_lines: &[String], reserved: usize, clear_rows: usize) -> Placement {
+        let border_color = Display::get_color(&self.config.display.border_color);
+        let column = self.config.display.image_size.width as usize + self.config.display.padding as usize + 1;
+        let show_border = self.config.display.show_border;
         
-        Ok(())
+        let mut rows = Vec::new();
+        if show_border {
+            rows.push(self.render_border(&self.config.display.border_top, border_color));
+        }
+        rows.extend(info_lines.iter().cloned());
+        if show_border {
+            rows.push(self.render_border(&self.config.display.border_bottom, border_color));
+        }
+        
+        // Each row is cleared right of the image before it is written
+        let written = rows.len().max(clear_rows);
+        for index in 0..written {
+            if index > 0 {
+                print!("\x1b[1B");
+            }
+            print!("\x1b[{}G\x1b[K{}", column, rows.get(index).map_or("", String::as_str));
+        }
+        
+        // Move cursor to the last row of our reserved space, then past it
+        let last_row = written.saturating_sub(1);
+        let last_reserved = reserved.saturating_sub(1);
+        if last_reserved > last_row {
+            print!("\x1b[{}B", last_reserved - last_row);
+        } else if last_row > last_reserved {
+            print!("\x1b[{}A", last_row - last_reserved);
+        }
+        print!("\x1b[1G"); // Move cursor to beginning of line
+        println!();
+        
+        let top = if show_border { 1 } else { 0 };
+        Placement {
+            block_rows: reserved,
+            first_line_rows: reserved - top,
+            column,
+            fixed_image: true,
+        }
     }
     
-    fn show_horizontal_layout_with_blocks(&self, image_path: &std::path::Path, info_lines: &[String]) -> Result<()> {
+    fn show_horizontal_layout_with_blocks(&self, image_path: &std::path::Path, info_lines: &[String]) -> Result<Placement> {
         let border_color = Display::get_color(&self.config.display.border_color);
         
         // Use block-based rendering for true side-by-side layout
@@ -422,10 +521,16 @@ impl Display {
             println!("{}", border_bottom_line);
         }
         
-        Ok(())
+        let border = if self.config.display.show_border { 1 } else { 0 };
+        Ok(Placement {
+            block_rows: border + max_lines + border,
+            first_line_rows: max_lines + border,
+            column: image_width + padding as usize + 1,
+            fixed_image: false,
+        })
     }
 
-    fn show_info_only(&self, info_lines: &[String]) {
+    fn show_info_only(&self, info_lines: &[String]) -> Placement {
         let border_color = Display::get_color(&self.config.display.border_color);
         
         // Render top border if enabled
@@ -449,51 +554,29 @@ impl Display {
         for _ in 0..self.config.display.padding {
             println!();
         }
-    }
-    
-    fn get_module_display_name(&self, module_key: &str, default_name: &str) -> String {
-        let display_names = &self.config.modules.display_names;
-        let custom_name = match module_key {
-            "user_at_host" => &display_names.user_at_host,
-            "os" => &display_names.os,
-            "kernel" => &display_names.kernel,
-            "linux" => &display_names.linux,
-            "uptime" => &display_names.uptime,
-            "os_age" => &display_names.os_age,
-            "packages" | "packages_combined" => &display_names.packages,
-            "shell" => &display_names.shell,
-            "resolution" => &display_names.resolution,
-            "network" => &display_names.network,
-            "public_ip" => &display_names.public_ip,
-            "de" => &display_names.de,
-            "wm" => &display_names.wm,
-            "theme" => &display_names.theme,
-            "icons" => &display_names.icons,
-            "terminal" => &display_names.terminal,
-            "terminal_shell_combined" => &display_names.terminal_shell_combined,
-            "font" => &display_names.font,
-            "user" => &display_names.user,
-            "hostname" => &display_names.hostname,
-            "cpu" => &display_names.cpu,
-            "cpu_temp" => &display_names.cpu_temp,
-            "gpu" => &display_names.gpu,
-            "gpu_temp" => &display_names.gpu_temp,
-            "temp_combined" => &display_names.temp_combined,
-            "gpu_driver" => &display_names.gpu_driver,
-            "memory" => &display_names.memory,
-            "disk" => &display_names.disk,
-            "dysk" => &display_names.dysk,
-            "battery" => &display_names.battery,
-            "locale" => &display_names.locale,
-            _ => &None,
-        };
         
-        custom_name.as_ref().unwrap_or(&default_name.to_string()).clone()
+        // Border lines come with a blank line each
+        let border = if self.config.display.show_border { 2 } else { 0 };
+        let below = border + self.config.display.padding as usize;
+        Placement {
+            block_rows: border + info_lines.len() + below,
+            first_line_rows: info_lines.len() + below,
+            column: 1,
+            fixed_image: false,
+        }
     }
-
-
-
+    
     fn prepare_system_info_lines(&self, system_info: &SystemInfo) -> Vec<String> {
+        self.build_info_lines(system_info, None)
+    }
+    
+    // While collecting, modules still pending show a placeholder and modules with
+    // nothing to show keep an empty line, so the line count stays stable
+    fn prepare_progress_lines(&self, collection: &Collection) -> Vec<String> {
+        self.build_info_lines(collection.info(), Some(&|name| collection.is_pending(name)))
+    }
+    
+    fn build_info_lines(&self, system_info: &SystemInfo, pending: Option<&dyn Fn(&str) -> bool>) -> Vec<String> {
         let modules = &self.config.modules;
         let colors = &self.config.general.colors;
         let separator_config = &self.config.general.separator;
@@ -507,50 +590,19 @@ impl Display {
             " ".repeat(separator_config.space_after as usize)
         );
 
-        // Define the order and mapping of modules
-        let module_order = [
-            ("user_at_host", "Login", modules.user_at_host),
-            ("user", "User", modules.user),
-            ("hostname", "Hostname", modules.hostname),            
-            ("os", "OS", modules.os),
-            ("kernel", "Kernel", modules.kernel),
-            ("linux", "Linux", modules.linux),
-            ("uptime", "Uptime", modules.uptime),
-            ("os_age", "OS Age", modules.os_age),
-            ("packages", "Packages", modules.packages),
-            ("flatpak_packages", "Flatpak", modules.flatpak_packages),
-            ("packages_combined", "Packages", modules.packages_combined),
-            ("shell", "Shell", modules.shell),
-            ("terminal", "Terminal", modules.terminal),
-            ("terminal_shell_combined", "Terminal & Shell", modules.terminal_shell_combined),            
-            ("resolution", "Resolution", modules.resolution),
-            ("de", "DE", modules.de),
-            ("wm", "WM", modules.wm),
-            ("theme", "Theme", modules.theme),
-            ("icons", "Icons", modules.icons),
-            ("font", "Font", modules.font),
-            ("locale", "Locale", modules.locale),            
-            ("cpu", "CPU", modules.cpu),
-            ("cpu_temp", "CPU Temp", modules.cpu_temp),
-            ("gpu", "GPU", modules.gpu),
-            ("gpu_temp", "GPU Temp", modules.gpu_temp),
-            ("gpu_driver", "GPU Driver", modules.gpu_driver),
-            ("temp_combined", "Temperatures", modules.temp_combined),            
-            ("memory", "Memory", modules.memory),
-            ("battery", "Battery", modules.battery),
-            ("network", "Network", modules.network),
-            ("public_ip", "Public IP", modules.public_ip),
-            ("disk", "Disk", modules.disk),            
-            ("dysk", "Drives", modules.dysk),
-        ];
+        // Configured layout: enabled modules plus separators and group headers
+        let layout = layout(modules);
         
         // Calculate maximum module name width for alignment if enabled
         let max_name_width = if separator_config.align_separator {
-            module_order
+            layout
                 .iter()
-                .filter(|(_, _, enabled)| *enabled)
-                .map(|(key, default_name, _)| {
-                    let display_name = self.get_module_display_name(key, default_name);
+                .filter_map(|entry| match entry {
+                    LayoutEntry::Module(module) => Some(*module),
+                    _ => None,
+                })
+                .map(|module| {
+                    let display_name = modules.display_name(module);
                     // Use standard unicode width for Nerd Font icons (consistent width)
                     display_name.width()
                 })
@@ -560,62 +612,63 @@ impl Display {
             0
         };
 
-        for (key, default_name, enabled) in module_order {
-            if enabled {
-                let lookup_key = key.to_uppercase();
-                if let Some(value) = system_info.data.get(&lookup_key) {
-                    let trimmed_value = value.trim();
-                    // Only add non-empty, non-Unknown values
-                    if !trimmed_value.is_empty() && trimmed_value != "Unknown" {
-                        // Get custom display name (with potential icon)
-                        let display_name = self.get_module_display_name(key, default_name);
-                        
-                        // Pad module name for alignment if enabled
-                        let padded_name = if separator_config.align_separator {
-                            // Calculate visual width and pad accordingly
-                            let visual_width = display_name.width();
-                            let padding_needed = max_name_width.saturating_sub(visual_width);
-                            format!("{}{}", display_name, " ".repeat(padding_needed))
-                        } else {
-                            display_name
-                        };
-                        
-                        // Handle multi-line modules (like dysk)
-                        let value_lines: Vec<&str> = trimmed_value.lines().collect();
-                        
-                        if value_lines.len() > 1 {
-                            // Multi-line module: first line with module name, subsequent lines indented
-                            for (i, value_line) in value_lines.iter().enumerate() {
-                                if i == 0 {
-                                    // First line with module name
-                                    let line = format!(
-                                        "{}{}{}",
-                                        self.apply_color(&padded_name, &colors.module),
-                                        self.apply_color(&separator, &colors.separator),
-                                        self.apply_color(value_line, &colors.info)
-                                    );
-                                    lines.push(line);
-                                } else {
-                                    // Subsequent lines: indent to align with the value column
-                                    let indent_width = padded_name.width() + separator.width();
-                                    let indented_line = format!(
-                                        "{}{}",
-                                        " ".repeat(indent_width),
-                                        self.apply_color(value_line, &colors.info)
-                                    );
-                                    lines.push(indented_line);
-                                }
-                            }
-                        } else {
-                            // Single-line module
-                            let line = format!(
-                                "{}{}{}",
-                                self.apply_color(&padded_name, &colors.module),
-                                self.apply_color(&separator, &colors.separator),
-                                self.apply_color(trimmed_value, &colors.info)
-                            );
-                            lines.push(line);
-                        }
+        for entry in layout {
+            let module = match entry {
+                LayoutEntry::Module(module) => module,
+                LayoutEntry::Separator => {
+                    lines.push(String::new());
+                    continue;
+                }
+                LayoutEntry::Header(title) => {
+                    lines.push(self.apply_color(&title, &colors.title).bold().to_string());
+                    continue;
+                }
+            };
+            
+            // Modules with nothing available are left out
+            let value_lines = match pending {
+                Some(pending) if pending(module.name()) => Some(vec![(PLACEHOLDER.to_string(), None)]),
+                // Fast modules are done before the first draw, so only slow ones need a stable line
+                Some(_) if module.cost() == Cost::Slow => {
+                    Some(system_info.render_lines(module, modules).unwrap_or_else(|| vec![(String::new(), None)]))
+                }
+                _ => system_info.render_lines(module, modules),
+            };
+            if let Some(value_lines) = value_lines {
+                // Get custom display name (with potential icon)
+                let display_name = modules.display_name(module);
+                
+                // Pad module name for alignment if enabled
+                let padded_name = if separator_config.align_separator {
+                    // Calculate visual width and pad accordingly
+                    let visual_width = display_name.width();
+                    let padding_needed = max_name_width.saturating_sub(visual_width);
+                    format!("{}{}", display_name, " ".repeat(padding_needed))
+                } else {
+                    display_name
+                };
+                
+                // Multi-line modules (like dysk): first line with module name,
+                // subsequent lines indented to align with the value column
+                for (i, (value_line, color)) in value_lines.iter().enumerate() {
+                    // Entries may override the info color, e.g. sensors near their limit
+                    let info_color = color.unwrap_or(&colors.info);
+                    if i == 0 {
+                        let line = format!(
+                            "{}{}{}",
+                            self.apply_color(&padded_name, &colors.module),
+                            self.apply_color(&separator, &colors.separator),
+                            self.apply_color(value_line.trim(), info_color)
+                        );
+                        lines.push(line);
+                    } else {
+                        let indent_width = padded_name.width() + separator.width();
+                        let indented_line = format!(
+                            "{}{}",
+                            " ".repeat(indent_width),
+                            self.apply_color(value_line.trim_end(), info_color)
+                        );
+                        lines.push(indented_line);
                     }
                 }
             }
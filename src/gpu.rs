@@ -0,0 +1,358 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use crate::cache;
+use crate::config::CacheConfig;
+use crate::kmod::KernelModule;
+use crate::sysroot;
+
+const PCI_IDS_PATHS: &[&str] = &[
+    "/usr/share/hwdata/pci.ids",
+    "/usr/share/misc/pci.ids",
+    "/usr/share/pci.ids",
+    "/var/lib/pciutils/pci.ids",
+];
+
+// Static GPU details from the previous run, so pci.ids is only searched when the hardware changes
+const KNOWN_FILE: &str = "gpus.json";
+
+// Firmware framebuffers and virtual devices that show up as DRM cards next to the real GPU
+const PSEUDO_DRIVERS: &[&str] = &["simple-framebuffer", "simpledrm", "efi-framebuffer", "vkms"];
+
+/// A display device from `/sys/class/drm/cardN`
+#[derive(Debug, Clone)]
+pub struct Gpu {
+    /// `card0`, `card1`, ...
+    pub card: String,
+    /// PCI address such as `0000:03:00.0`; `None` for SoC GPUs
+    pub pci_slot: Option<String>,
+    pub vendor_id: Option<u16>,
+    pub device_id: Option<u16>,
+    /// Name from pci.ids, or the driver for SoC GPUs; see `name()`
+    name: Option<String>,
+    /// Kernel driver bound to the device (`amdgpu`, `i915`, `nvidia`, ...)
+    pub driver: Option<String>,
+    /// Kernel module providing the driver
+    pub module: Option<KernelModule>,
+    /// The GPU the firmware initialized, i.e. the one driving the boot display
+    pub boot_vga: bool,
+    /// VRAM in bytes as reported by the driver (amdgpu only)
+    pub vram_total: Option<u64>,
+    pub vram_used: Option<u64>,
+    /// Load in percent (amdgpu only; nvidia-smi covers NVIDIA)
+    pub busy_percent: Option<f64>,
+    /// Current clocks in Hz
+    pub core_clock: Option<f64>,
+    pub memory_clock: Option<f64>,
+    /// Board or package power draw in watts, from the GPU's hwmon device
+    pub power: Option<f64>,
+}
+
+impl Gpu {
+    fn read(card: String, known: &[StaticInfo]) -> Option<Self> {
+        let device = PathBuf::from("/sys/class/drm").join(&card).join("device");
+        let read = |attribute: &str| sysroot::read_to_string(device.join(attribute)).ok().map(|value| value.trim().to_string());
+        let hex = |attribute: &str| u32::from_str_radix(read(attribute)?.trim_start_matches("0x"), 16).ok();
+        let bytes = |attribute: &str| read(attribute)?.parse::<u64>().ok();
+
+        let pci_slot = read("uevent").and_then(|uevent| {
+            uevent.lines().find_map(|line| line.strip_prefix("PCI_SLOT_NAME=")).map(str::to_string)
+        });
+        let vendor_id = hex("vendor").map(|id| id as u16);
+        let device_id = hex("device").map(|id| id as u16);
+
+        // Only display controllers (PCI class 0x03xxxx)
+        if pci_slot.is_some() && hex("class").is_some_and(|class| class >> 16 != 0x03) {
+            return None;
+        }
+
+        let info = match known.iter().find(|info| info.is(&card, &pci_slot, vendor_id, device_id)) {
+            Some(info) => info.clone(),
+            None => {
+                let driver = sysroot::resolve(device.join("driver"))
+                    .read_link()
+                    .ok()
+                    .and_then(|target| Some(target.file_name()?.to_str()?.to_string()));
+                let name = match (vendor_id, device_id) {
+                    (Some(vendor_id), Some(device_id)) => lookup_name(vendor_id, device_id),
+                    // SoC GPUs have no PCI IDs; the driver (panfrost, msm, ...) is the best name there is
+                    _ => Some(driver.clone().unwrap_or_else(|| card.clone())),
+                };
+                StaticInfo {
+                    card: card.clone(),
+                    pci_slot: pci_slot.clone(),
+                    vendor_id,
+                    device_id,
+                    name,
+                    driver,
+                    vram_total: bytes("mem_info_vram_total").filter(|&total| total > 0),
+                }
+            }
+        };
+        if pci_slot.is_none() && info.driver.as_deref().is_some_and(|driver| PSEUDO_DRIVERS.contains(&driver)) {
+            return None;
+        }
+
+        // i915 reports 0 while the GPU is in RC6 sleep
+        let intel_clock = sysroot::read_to_string(Path::new("/sys/class/drm").join(&card).join("gt_act_freq_mhz"))
+            .ok()
+            .and_then(|mhz| mhz.trim().parse::<f64>().ok())
+            .filter(|&mhz| mhz > 0.0)
+            .map(|mhz| mhz * 1_000_000.0);
+        Some(Self {
+            pci_slot,
+            vendor_id,
+            device_id,
+            name: info.name,
+            driver: info.driver,
+            module: KernelModule::of_device(&device),
+            boot_vga: read("boot_vga").as_deref() == Some("1"),
+            vram_total: info.vram_total,
+            vram_used: bytes("mem_info_vram_used"),
+            busy_percent: read("gpu_busy_percent").and_then(|percent| percent.parse().ok()),
+            core_clock: read("pp_dpm_sclk").as_deref().and_then(active_clock).or(intel_clock),
+            memory_clock: read("pp_dpm_mclk").as_deref().and_then(active_clock),
+            power: power_draw(&device),
+            card,
+        })
+    }
+
+    /// Marketing name from pci.ids, e.g. "NVIDIA GeForce RTX 4070 SUPER" for
+    /// "AD104 [GeForce RTX 4070 SUPER]"; the raw IDs if the database is missing
+    pub fn name(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        let vendor_id = self.vendor_id.unwrap_or_default();
+        let device_id = self.device_id.unwrap_or_default();
+        let vendor = vendor_name(vendor_id).unwrap_or_else(|| format!("{:04x}", vendor_id));
+        format!("{} GPU [{:04x}:{:04x}]", vendor, vendor_id, device_id)
+    }
+
+    fn static_info(&self) -> StaticInfo {
+        StaticInfo {
+            card: self.card.clone(),
+            pci_slot: self.pci_slot.clone(),
+            vendor_id: self.vendor_id,
+            device_id: self.device_id,
+            name: self.name.clone(),
+            driver: self.driver.clone(),
+            vram_total: self.vram_total,
+        }
+    }
+}
+
+/// What is kept of a GPU between runs: everything that only changes with the hardware
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct StaticInfo {
+    card: String,
+    pci_slot: Option<String>,
+    vendor_id: Option<u16>,
+    device_id: Option<u16>,
+    name: Option<String>,
+    driver: Option<String>,
+    vram_total: Option<u64>,
+}
+
+impl StaticInfo {
+    // Still the same device in the same slot
+    fn is(&self, card: &str, pci_slot: &Option<String>, vendor_id: Option<u16>, device_id: Option<u16>) -> bool {
+        self.card == card && &self.pci_slot == pci_slot && self.vendor_id == vendor_id && self.device_id == device_id
+    }
+}
+
+/// Static details of the GPUs as stored in the cache directory
+#[derive(Serialize, Deserialize)]
+struct KnownGpus {
+    /// Unix time in seconds when the details were read
+    stored: u64,
+    gpus: Vec<StaticInfo>,
+}
+
+fn unix_time(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or(0)
+}
+
+// Stored details, unless a card was added or removed since
+fn load_known() -> Vec<StaticInfo> {
+    let known = cache::path(KNOWN_FILE)
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<KnownGpus>(&content).ok());
+    let Some(known) = known else {
+        return Vec::new();
+    };
+    let modified = sysroot::metadata("/sys/class/drm").and_then(|metadata| metadata.modified()).ok();
+    if modified.is_none_or(|modified| unix_time(modified) >= known.stored) {
+        return Vec::new();
+    }
+    known.gpus
+}
+
+// amdgpu lists its clock levels as "0: 500Mhz", "1: 2100Mhz *"; the starred one is current
+fn active_clock(levels: &str) -> Option<f64> {
+    let level = levels.lines().find(|line| line.trim_end().ends_with('*'))?;
+    let mhz = level.split_whitespace().nth(1)?.to_ascii_lowercase();
+    let mhz: f64 = mhz.strip_suffix("mhz")?.parse().ok()?;
+    Some(mhz * 1_000_000.0)
+}
+
+// amdgpu and nouveau register a hwmon device below the PCI device
+fn power_draw(device: &Path) -> Option<f64> {
+    sysroot::read_dir(device.join("hwmon")).ok()?.iter().find_map(|hwmon| {
+        ["power1_average", "power1_input"].iter().find_map(|attribute| {
+            let microwatts: f64 = sysroot::read_to_string(hwmon.join(attribute)).ok()?.trim().parse().ok()?;
+            Some(microwatts / 1_000_000.0)
+        })
+    })
+}
+
+/// All GPUs, primary (boot) GPU first, read once per run. With caching on,
+/// names, drivers and VRAM sizes are reused from the previous run.
+pub fn gpus(cache: &CacheConfig) -> &'static [Gpu] {
+    static GPUS: OnceLock<Vec<Gpu>> = OnceLock::new();
+    GPUS.get_or_init(|| {
+        let mut cards: Vec<(u32, String)> = sysroot::read_dir("/sys/class/drm")
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|path| path.file_name()?.to_str())
+            // card1-DP-2 etc. are connectors
+            .filter_map(|name| Some((name.strip_prefix("card")?.parse().ok()?, name.to_string())))
+            .collect();
+        cards.sort();
+
+        let known = if cache.is_active() { load_known() } else { Vec::new() };
+        let mut gpus: Vec<Gpu> = cards.into_iter().filter_map(|(_, card)| Gpu::read(card, &known)).collect();
+        gpus.sort_by_key(|gpu| !gpu.boot_vga);
+
+        // Names that fell back to raw IDs are looked up again once pci.ids is installed
+        let complete = gpus.iter().all(|gpu| gpu.name.is_some());
+        let looked_up = gpus.iter().any(|gpu| !known.iter().any(|info| info.is(&gpu.card, &gpu.pci_slot, gpu.vendor_id, gpu.device_id)));
+        if cache.is_active() && complete && looked_up {
+            let known = KnownGpus {
+                stored: unix_time(SystemTime::now()),
+                gpus: gpus.iter().map(Gpu::static_info).collect(),
+            };
+            if let Ok(content) = serde_json::to_string(&known) {
+                let _ = cache::write(KNOWN_FILE, &content);
+            }
+        }
+        gpus
+    })
+}
+
+/// Bus, device and function of a PCI address in lower case, e.g. `01:00.0` for
+/// `0000:01:00.0`; nvidia-smi writes the domain with eight digits (`00000000:01:00.0`)
+pub fn bus_address(slot: &str) -> String {
+    let address = slot.split_once(':').filter(|(_, rest)| rest.contains(':')).map_or(slot, |(_, rest)| rest);
+    address.to_ascii_lowercase()
+}
+
+fn pci_database() -> Option<&'static str> {
+    static DATABASE: OnceLock<Option<String>> = OnceLock::new();
+    DATABASE
+        .get_or_init(|| PCI_IDS_PATHS.iter().find_map(|path| sysroot::read_to_string(path).ok()))
+        .as_deref()
+}
+
+// Short vendor name used in front of the model
+fn vendor_name(vendor_id: u16) -> Option<String> {
+    match vendor_id {
+        0x10de => Some("NVIDIA".to_string()),
+        0x1002 => Some("AMD".to_string()),
+        0x8086 => Some("Intel".to_string()),
+        0x1af4 => Some("Virtio".to_string()),
+        0x15ad => Some("VMware".to_string()),
+        vendor => lookup_pci_ids(vendor, None).map(|(vendor, _)| vendor),
+    }
+}
+
+// Vendor and product name, e.g. "NVIDIA GeForce RTX 4070 SUPER" for
+// "AD104 [GeForce RTX 4070 SUPER]"; `None` if pci.ids doesn't know the device
+fn lookup_name(vendor_id: u16, device_id: u16) -> Option<String> {
+    let model = lookup_pci_ids(vendor_id, Some(device_id))?.1?;
+    let vendor = vendor_name(vendor_id).unwrap_or_else(|| format!("{:04x}", vendor_id));
+    // The part in brackets is the product name; before it is the chip codename
+    let model = match (model.find('['), model.rfind(']')) {
+        (Some(start), Some(end)) if start < end => model[start + 1..end].to_string(),
+        _ => model,
+    };
+    Some(format!("{} {}", vendor, model))
+}
+
+// Vendor name and, if asked for and known, device name. pci.ids lists vendors at
+// column 0 ("10de  NVIDIA Corporation"), their devices after one tab and
+// subsystems after two.
+fn lookup_pci_ids(vendor: u16, device: Option<u16>) -> Option<(String, Option<String>)> {
+    find_pci_ids(pci_database()?, vendor, device)
+}
+
+fn find_pci_ids(database: &str, vendor: u16, device: Option<u16>) -> Option<(String, Option<String>)> {
+    let parse = |line: &str| {
+        let (id, name) = line.split_once("  ")?;
+        Some((u16::from_str_radix(id, 16).ok()?, name.trim().to_string()))
+    };
+
+    let mut lines = database.lines();
+    let vendor_name = lines.find_map(|line| parse(line).filter(|(id, _)| *id == vendor))?.1;
+    let Some(device) = device else {
+        return Some((vendor_name, None));
+    };
+    let device_name = lines
+        // Stop at the next vendor
+        .take_while(|line| line.is_empty() || line.starts_with('\t') || line.starts_with('#'))
+        .filter(|line| !line.starts_with("\t\t"))
+        .find_map(|line| parse(line.strip_prefix('\t')?).filter(|(id, _)| *id == device))
+        .map(|(_, name)| name);
+    Some((vendor_name, device_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PCI_IDS: &str = "\
+#\tList of PCI ID's
+1002  Advanced Micro Devices, Inc. [AMD/ATI]
+\t73bf  Navi 21 [Radeon RX 6800/6800 XT / 6900 XT]
+\t\t1002 0e3a  Radeon RX 6900 XT
+10de  NVIDIA Corporation
+# Comments may sit between devices
+\t2783  AD104 [GeForce RTX 4070 SUPER]
+8086  Intel Corporation
+\t46a6  Alder Lake-P GT2 [Iris Xe Graphics]
+C 03  Display controller
+\t00  VGA compatible controller
+";
+
+    #[test]
+    fn looks_up_vendors_and_devices() {
+        let (vendor, device) = find_pci_ids(PCI_IDS, 0x10de, Some(0x2783)).unwrap();
+        assert_eq!(vendor, "NVIDIA Corporation");
+        assert_eq!(device.as_deref(), Some("AD104 [GeForce RTX 4070 SUPER]"));
+        assert_eq!(find_pci_ids(PCI_IDS, 0x1002, None).unwrap().1, None);
+    }
+
+    #[test]
+    fn does_not_match_devices_of_other_vendors_or_subsystems() {
+        // 0e3a is only a subsystem of 73bf, and 46a6 belongs to Intel
+        assert_eq!(find_pci_ids(PCI_IDS, 0x1002, Some(0x0e3a)).unwrap().1, None);
+        assert_eq!(find_pci_ids(PCI_IDS, 0x10de, Some(0x46a6)).unwrap().1, None);
+        assert!(find_pci_ids(PCI_IDS, 0x1234, Some(0x0001)).is_none());
+    }
+
+    #[test]
+    fn reads_the_active_clock_level() {
+        assert_eq!(active_clock("0: 500Mhz\n1: 1000Mhz\n2: 2105Mhz *\n"), Some(2_105_000_000.0));
+        assert_eq!(active_clock("0: 96Mhz *\n1: 1000Mhz\n"), Some(96_000_000.0));
+        assert_eq!(active_clock("0: 500Mhz\n1: 1000Mhz\n"), None);
+    }
+
+    #[test]
+    fn shortens_bus_addresses() {
+        assert_eq!(bus_address("0000:03:00.0"), "03:00.0");
+        assert_eq!(bus_address("00000000:0A:00.0"), "0a:00.0");
+        assert_eq!(bus_address("01:00.0"), "01:00.0");
+    }
+}
@@ -0,0 +1,274 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use crate::sysroot;
+
+/// What a temperature sensor measures, derived from its chip name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Cpu,
+    Gpu,
+    Storage,
+    Memory,
+    /// Mainboard, chipset and ACPI zones
+    Board,
+    Other,
+}
+
+impl Role {
+    fn of_chip(chip: &str) -> Self {
+        match chip {
+            "k10temp" | "coretemp" | "zenpower" | "x86_pkg_temp" | "cpu_thermal" | "cpu-thermal" | "soc_thermal" => Role::Cpu,
+            "amdgpu" | "radeon" | "nouveau" | "xe" | "i915" | "gpu_thermal" | "gpu-thermal" => Role::Gpu,
+            "nvme" | "drivetemp" => Role::Storage,
+            "spd5118" | "jc42" => Role::Memory,
+            "acpitz" | "pch_skylake" | "pch_cannonlake" | "pch_cometlake" => Role::Board,
+            chip if is_super_io(chip) => Role::Board,
+            _ => Role::Other,
+        }
+    }
+}
+
+// Super I/O chips on the mainboard (nct6775, nct6798, it8686, ...)
+fn is_super_io(chip: &str) -> bool {
+    chip.starts_with("nct") || chip.starts_with("it87") || chip.starts_with("it86")
+}
+
+/// One temperature reading from hwmon or a thermal zone
+#[derive(Debug, Clone)]
+pub struct Temperature {
+    /// hwmon `name` (`k10temp`, `nvme`, ...) or thermal zone `type`
+    pub chip: String,
+    /// `temp*_label` (`Tctl`, `Composite`, ...), or `tempN` if the driver has none
+    pub label: String,
+    pub celsius: f64,
+    /// Kernel-provided limits (`temp*_max`, `temp*_crit` or a critical trip point)
+    pub max: Option<f64>,
+    pub crit: Option<f64>,
+    pub role: Role,
+}
+
+impl Temperature {
+    /// Chip and label for display, e.g. "k10temp Tctl"; just the chip if the
+    /// driver has no label of its own ("acpitz" rather than "acpitz temp1")
+    pub fn name(&self) -> String {
+        let generic = self.label == self.chip
+            || self.label.strip_prefix("temp").is_some_and(|rest| rest.chars().all(|c| c.is_ascii_digit()));
+        if generic {
+            self.chip.clone()
+        } else {
+            format!("{} {}", self.chip, self.label)
+        }
+    }
+
+    /// Whether a config selector such as `k10temp`, `k10temp/Tctl` or `nvme/Composite` names this sensor.
+    /// Chip and label are compared case-insensitively.
+    pub fn matches(&self, selector: &str) -> bool {
+        let (chip, label) = match selector.split_once('/') {
+            Some((chip, label)) => (chip, Some(label)),
+            None => (selector, None),
+        };
+        self.chip.eq_ignore_ascii_case(chip) && label.is_none_or(|label| self.label.eq_ignore_ascii_case(label))
+    }
+}
+
+/// A `/sys/class/hwmon/hwmonN` device
+#[derive(Debug, Clone)]
+pub struct Chip {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl Chip {
+    /// Channel numbers that have an attribute, e.g. 1 and 2 for `temp1_input` and
+    /// `temp2_input` when asked for `("temp", "input")`
+    pub fn channels(&self, kind: &str, attribute: &str) -> Vec<u32> {
+        let suffix = format!("_{}", attribute);
+        let mut channels: Vec<u32> = sysroot::read_dir(&self.path)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|path| path.file_name()?.to_str())
+            .filter_map(|file| file.strip_prefix(kind)?.strip_suffix(suffix.as_str())?.parse().ok())
+            .collect();
+        channels.sort_unstable();
+        channels
+    }
+
+    pub fn role(&self) -> Role {
+        Role::of_chip(&self.name)
+    }
+
+    /// Raw integer value of an attribute such as `temp1_input`
+    pub fn read(&self, attribute: &str) -> Option<i64> {
+        read_number(&self.path.join(attribute))
+    }
+
+    pub fn label(&self, kind: &str, channel: u32) -> Option<String> {
+        let label = sysroot::read_to_string(self.path.join(format!("{}{}_label", kind, channel))).ok()?;
+        Some(label.trim().to_string()).filter(|label| !label.is_empty())
+    }
+}
+
+fn read_number(path: &Path) -> Option<i64> {
+    sysroot::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// All hwmon devices, read once per run
+pub fn chips() -> &'static [Chip] {
+    static CHIPS: OnceLock<Vec<Chip>> = OnceLock::new();
+    CHIPS.get_or_init(|| {
+        let mut paths = sysroot::read_dir("/sys/class/hwmon").unwrap_or_default();
+        // hwmon10 after hwmon9
+        paths.sort_by_key(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str()?.strip_prefix("hwmon")?.parse::<u32>().ok())
+                .unwrap_or(u32::MAX)
+        });
+        paths
+            .into_iter()
+            .filter_map(|path| {
+                let name = sysroot::read_to_string(path.join("name")).ok()?.trim().to_string();
+                Some(Chip { name, path })
+            })
+            .collect()
+    })
+}
+
+/// Every temperature sensor: hwmon first, then thermal zones that have no hwmon counterpart
+pub fn temperatures() -> &'static [Temperature] {
+    static TEMPERATURES: OnceLock<Vec<Temperature>> = OnceLock::new();
+    TEMPERATURES.get_or_init(|| {
+        let millidegrees = |value: i64| value as f64 / 1000.0;
+        // Some drivers report unset limits as 0, negative values or placeholders like 255.5°C
+        let limit = |value: i64| Some(millidegrees(value)).filter(|&celsius| celsius > 25.0 && celsius <= 150.0);
+        let mut temperatures = Vec::new();
+
+        for chip in chips() {
+            for channel in chip.channels("temp", "input") {
+                let Some(input) = chip.read(&format!("temp{}_input", channel)) else {
+                    continue;
+                };
+                temperatures.push(Temperature {
+                    chip: chip.name.clone(),
+                    label: chip.label("temp", channel).unwrap_or_else(|| format!("temp{}", channel)),
+                    celsius: millidegrees(input),
+                    max: chip.read(&format!("temp{}_max", channel)).and_then(limit),
+                    crit: chip.read(&format!("temp{}_crit", channel)).and_then(limit),
+                    role: chip.role(),
+                });
+            }
+        }
+
+        // Most zones (acpitz, ...) are also registered as hwmon devices
+        let mut zones = sysroot::read_dir("/sys/class/thermal").unwrap_or_default();
+        zones.sort();
+        let is_zone = |path: &PathBuf| path.file_name().and_then(|name| name.to_str()).is_some_and(|name| name.starts_with("thermal_zone"));
+        for zone in zones.into_iter().filter(is_zone) {
+            let Ok(kind) = sysroot::read_to_string(zone.join("type")).map(|kind| kind.trim().to_string()) else {
+                continue;
+            };
+            if chips().iter().any(|chip| chip.name == kind) {
+                continue;
+            }
+            // Disabled zones report 0 or garbage
+            let Some(input) = read_number(&zone.join("temp")).filter(|&input| input > 0) else {
+                continue;
+            };
+            temperatures.push(Temperature {
+                label: kind.clone(),
+                celsius: millidegrees(input),
+                max: None,
+                crit: critical_trip_point(&zone).and_then(limit),
+                role: Role::of_chip(&kind),
+                chip: kind,
+            });
+        }
+
+        temperatures
+    })
+}
+
+// Zones list their limits as trip_point_N_type / trip_point_N_temp pairs
+fn critical_trip_point(zone: &Path) -> Option<i64> {
+    (0..16).find_map(|index| {
+        let kind = sysroot::read_to_string(zone.join(format!("trip_point_{}_type", index))).ok()?;
+        if kind.trim() != "critical" {
+            return None;
+        }
+        read_number(&zone.join(format!("trip_point_{}_temp", index)))
+    })
+}
+
+/// A fan or power sensor of a hwmon chip
+#[derive(Debug, Clone)]
+pub struct Reading<'a> {
+    pub chip: &'a Chip,
+    pub channel: u32,
+    /// `fan*_label` / `power*_label`, if the driver provides one
+    pub label: Option<String>,
+    /// RPM for fans, watts for power
+    pub value: f64,
+}
+
+impl Reading<'_> {
+    /// What an unlabeled fan cools, judged by its chip: CPU and GPU drivers
+    /// only report their own fan, and Super I/O chips wire `fan1` to the CPU header
+    pub fn cools(&self) -> Option<Role> {
+        match self.chip.role() {
+            role @ (Role::Cpu | Role::Gpu) => Some(role),
+            _ if is_super_io(&self.chip.name) && self.channel == 1 => Some(Role::Cpu),
+            _ => None,
+        }
+    }
+}
+
+/// Fan speeds from `fan*_input`
+pub fn fans() -> Vec<Reading<'static>> {
+    let mut fans = Vec::new();
+    for chip in chips() {
+        for channel in chip.channels("fan", "input") {
+            if let Some(rpm) = chip.read(&format!("fan{}_input", channel)) {
+                fans.push(Reading { chip, channel, label: chip.label("fan", channel), value: rpm as f64 });
+            }
+        }
+    }
+    fans
+}
+
+/// Power draw in watts from `power*_average`, or `power*_input` where drivers
+/// only report the instantaneous value
+pub fn power() -> Vec<Reading<'static>> {
+    let mut readings = Vec::new();
+    for chip in chips() {
+        let mut channels = chip.channels("power", "average");
+        channels.extend(chip.channels("power", "input"));
+        channels.sort_unstable();
+        channels.dedup();
+
+        for channel in channels {
+            let microwatts = chip
+                .read(&format!("power{}_average", channel))
+                .or_else(|| chip.read(&format!("power{}_input", channel)));
+            if let Some(microwatts) = microwatts {
+                let value = microwatts as f64 / 1_000_000.0;
+                readings.push(Reading { chip, channel, label: chip.label("power", channel), value });
+            }
+        }
+    }
+    readings
+}
+
+/// Sensor picked by a config selector (`chip` or `chip/label`)
+pub fn select(selector: &str) -> Option<&'static Temperature> {
+    temperatures().iter().find(|temperature| temperature.matches(selector))
+}
+
+/// Most representative sensor for a role: the package or die temperature
+/// for CPUs and the edge temperature for GPUs, otherwise the first one
+pub fn primary(role: Role) -> Option<&'static Temperature> {
+    const PREFERRED: &[&str] = &["Tctl", "Tdie", "Package id 0", "Core 0", "edge", "junction"];
+    let candidates = || temperatures().iter().filter(move |temperature| temperature.role == role);
+    PREFERRED
+        .iter()
+        .find_map(|label| candidates().find(|temperature| temperature.label == *label))
+        .or_else(|| candidates().next())
+}
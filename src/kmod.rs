@@ -0,0 +1,47 @@
+use std::path::Path;
+use crate::sysroot;
+
+/// A kernel module as seen in `/sys/module/<name>`
+#[derive(Debug, Clone)]
+pub struct KernelModule {
+    pub name: String,
+    /// `MODULE_VERSION` of the module; in-tree drivers usually have none
+    pub version: Option<String>,
+    /// Taint flags the module set when it was loaded, e.g. `POE` for the
+    /// proprietary NVIDIA driver; empty for in-tree modules
+    pub taint: String,
+}
+
+impl KernelModule {
+    /// The module if it is loaded or built into the kernel
+    pub fn get(name: &str) -> Option<Self> {
+        let dir = Path::new("/sys/module").join(name);
+        if !sysroot::exists(&dir) {
+            return None;
+        }
+        let read = |attribute: &str| {
+            sysroot::read_to_string(dir.join(attribute))
+                .ok()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        Some(Self {
+            name: name.to_string(),
+            version: read("version"),
+            taint: read("taint").unwrap_or_default(),
+        })
+    }
+
+    /// The module a device's driver belongs to, via `<device>/driver/module`
+    pub fn of_device(device: &Path) -> Option<Self> {
+        let target = sysroot::resolve(device.join("driver/module")).read_link().ok()?;
+        Self::get(target.file_name()?.to_str()?)
+    }
+
+    /// Whether the module's license is not GPL-compatible. The kernel sets the `P`
+    /// taint flag from `MODULE_LICENSE` on load, which is what `modinfo -F license`
+    /// would show, without having to find and decompress the `.ko`.
+    pub fn is_proprietary(&self) -> bool {
+        self.taint.contains('P')
+    }
+}
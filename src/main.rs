@@ -1,15 +1,33 @@
 use anyhow::Result;
 use clap::Parser;
+use std::io::{self, IsTerminal};
 use std::path::PathBuf;
 
 mod config;
 mod system_info;
 mod display;
 mod kitty_graphics;
+mod modules;
+mod template;
+mod value;
+mod output;
+mod cache;
+mod sysroot;
+mod commands;
+mod process;
+mod mounts;
+mod hwmon;
+mod gpu;
+mod kmod;
+mod net;
+mod public_ip;
+mod packages;
+mod updates;
 
 use config::{Config, MotdConfig};
 use system_info::SystemInfo;
 use display::Display;
+use output::OutputFormat;
 
 #[derive(Parser)]
 #[command(name = "termgreet")]
@@ -26,11 +44,35 @@ struct Cli {
     /// Disable image display
     #[arg(long)]
     no_image: bool,
+    
+    /// Output format; anything but text skips images, colors and the MOTD
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+    
+    /// Read /proc, /sys and /etc below this directory, e.g. a snapshot of another machine
+    #[arg(long, value_name = "DIR")]
+    sysroot: Option<PathBuf>,
+    
+    /// Save the output of every external command to a bundle file
+    #[arg(long, value_name = "FILE", conflicts_with = "replay")]
+    record: Option<PathBuf>,
+    
+    /// Take external command output from a recorded bundle instead of running anything
+    #[arg(long, value_name = "FILE")]
+    replay: Option<PathBuf>,
+    
+    /// Check for pending package updates and save the result for the updates module
+    #[arg(long, exclusive = true)]
+    refresh_updates: bool,
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
     
+    if cli.refresh_updates {
+        return updates::refresh();
+    }
+    
     let config_path = cli.config.unwrap_or_else(|| {
         dirs::config_dir()
             .unwrap_or_else(|| PathBuf::from(".config"))
@@ -40,12 +82,37 @@ fn main() -> Result<()> {
     
     let config = Config::load(&config_path)?;
     
+    if let Some(root) = cli.sysroot.or_else(|| config.general.sysroot.clone()) {
+        if !root.is_dir() {
+            return Err(anyhow::anyhow!("Sysroot {} is not a directory", root.display()));
+        }
+        sysroot::set(root);
+    }
+    
+    if let Some(path) = &cli.record {
+        commands::set_runner(Box::new(commands::RecordingRunner::create(path)?));
+    } else if let Some(path) = &cli.replay {
+        commands::set_runner(Box::new(commands::ReplayRunner::load(path)?));
+    }
+    
     if cli.motd {
         let motd_config = MotdConfig::load(&config.motd_file)?;
         Display::show_motd(&motd_config);
         return Ok(());
     }
     
+    if cli.format != OutputFormat::Text {
+        let system_info = SystemInfo::gather_with_config(&config);
+        return output::print(&system_info, &config, cli.format);
+    }
+    
+    // In-place updates need a terminal to move the cursor in
+    if config.display.progressive && io::stdout().is_terminal() {
+        let collection = SystemInfo::start_collection(&config);
+        let display = Display::new(config, !cli.no_image);
+        return display.show_progressive(collection);
+    }
+    
     let system_info = SystemInfo::gather_with_config(&config);
     let display = Display::new(config, !cli.no_image);
     
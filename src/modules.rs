@@ -0,0 +1,778 @@
+use serde::Serialize;
+use std::path::PathBuf;
+use std::time::Duration;
+use sysinfo::System;
+use crate::config::ModulesConfig;
+use crate::packages;
+use crate::system_info::SystemInfo;
+use crate::value::{Fields, Unit, Value};
+
+/// Rough cost class of a module, used to schedule collectors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cost {
+    /// Environment variables, small files or the sysinfo snapshot
+    Fast,
+    /// External commands or large directory walks
+    Slow,
+}
+
+/// Parts of the shared sysinfo snapshot a module needs refreshed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dependency {
+    Cpu,
+    Memory,
+}
+
+/// Everything a collector may need besides its own probing
+pub struct CollectContext<'a> {
+    pub sys: &'a System,
+    pub show_versions: bool,
+    pub config: &'a ModulesConfig,
+}
+
+/// A single line (or block) of system information.
+///
+/// Config keys, collection and display are all derived from the registry
+/// below, so adding a module only means adding a type here.
+pub trait Module: Sync {
+    /// Key used in `[modules]`, `[modules.display_names]` and `SystemInfo.data`
+    fn name(&self) -> &'static str;
+
+    /// Label shown when no display name is configured
+    fn default_label(&self) -> &'static str;
+
+    /// Key looked up in `[modules.display_names]`; modules may share one
+    fn display_name_key(&self) -> &'static str {
+        self.name()
+    }
+
+    /// Whether the module is enabled in a freshly generated config
+    fn enabled_by_default(&self) -> bool {
+        false
+    }
+
+    fn cost(&self) -> Cost {
+        Cost::Fast
+    }
+
+    fn dependencies(&self) -> &'static [Dependency] {
+        &[]
+    }
+
+    /// Format string used when `[modules.format]` has no entry for this module
+    fn default_format(&self) -> &'static str {
+        "{value}"
+    }
+
+    /// Placed between rendered entries of modules that report several
+    /// (e.g. one per mount point); `"\n"` renders one line per entry
+    fn entry_separator(&self) -> &'static str {
+        " • "
+    }
+
+    /// Whether the module can report several entries (one per mount point,
+    /// sensor, ...); machine-readable output always numbers those
+    fn multiple_entries(&self) -> bool {
+        false
+    }
+
+    /// Color for one entry's text instead of `general.colors.info`, e.g. to
+    /// highlight a sensor close to its limit
+    fn entry_color(&self, _fields: &Fields) -> Option<&'static str> {
+        None
+    }
+
+    /// How long collected entries may be reused from the on-disk cache;
+    /// `None` means the module is always collected fresh
+    fn cache_ttl(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Files or directories whose modification invalidates the cached entries
+    /// (e.g. a package database); a leading `~/` refers to the home directory
+    fn cache_inputs(&self) -> Vec<PathBuf> {
+        Vec::new()
+    }
+
+    /// The settings the module's entries depend on, e.g. its sub-config as JSON;
+    /// cached entries collected under different settings are not reused
+    fn cache_config(&self, _config: &ModulesConfig) -> String {
+        String::new()
+    }
+
+    /// Collect the module's entries; empty if the module does not apply to this system
+    fn collect(&self, ctx: &CollectContext) -> Vec<Fields>;
+}
+
+/// All known modules in their default display order
+pub fn registry() -> &'static [&'static dyn Module] {
+    static MODULES: &[&dyn Module] = &[
+        &UserAtHost,
+        &User,
+        &Hostname,
+        &Os,
+        &Kernel,
+        &Linux,
+        &Uptime,
+        &OsAge,
+        &LastUpdate,
+        &Updates,
+        &Packages,
+        &FlatpakPackages,
+        &PackagesCombined,
+        &Shell,
+        &Terminal,
+        &TerminalShellCombined,
+        &Multiplexer,
+        &Resolution,
+        &De,
+        &Wm,
+        &Theme,
+        &Icons,
+        &Font,
+        &Locale,
+        &Cpu,
+        &CpuTemp,
+        &Gpu,
+        &GpuTemp,
+        &GpuDriver,
+        &TempCombined,
+        &Sensors,
+        &FansPower,
+        &Memory,
+        &Battery,
+        &Network,
+        &Interfaces,
+        &PublicIp,
+        &Disk,
+        &Dysk,
+    ];
+    MODULES
+}
+
+// Sub-configs as `cache_config` strings
+fn json(config: &impl Serialize) -> String {
+    serde_json::to_string(config).unwrap_or_default()
+}
+
+// Constant `cache_inputs` lists as paths
+fn paths(inputs: &[&str]) -> Vec<PathBuf> {
+    inputs.iter().map(PathBuf::from).collect()
+}
+
+pub fn find(name: &str) -> Option<&'static dyn Module> {
+    registry().iter().copied().find(|module| module.name() == name)
+}
+
+/// One line of the configured output layout
+pub enum LayoutEntry {
+    Module(&'static dyn Module),
+    Separator,
+    Header(String),
+}
+
+/// Resolve `modules.order` (or the registry order) into the enabled layout.
+/// Unknown names are skipped; `Config::load` warns about them once.
+pub fn layout(config: &ModulesConfig) -> Vec<LayoutEntry> {
+    let Some(order) = &config.order else {
+        return registry()
+            .iter()
+            .copied()
+            .filter(|module| config.is_enabled(module.name()))
+            .map(LayoutEntry::Module)
+            .collect();
+    };
+
+    let mut entries = Vec::new();
+    for entry in order {
+        let entry = entry.trim();
+        if let Some(title) = entry.strip_prefix("---") {
+            let title = title.trim_matches(|c: char| c == '-' || c.is_whitespace());
+            if title.is_empty() {
+                entries.push(LayoutEntry::Separator);
+            } else {
+                entries.push(LayoutEntry::Header(title.to_string()));
+            }
+        } else if let Some(module) = find(entry)
+            && config.is_enabled(module.name())
+        {
+            entries.push(LayoutEntry::Module(module));
+        }
+    }
+    entries
+}
+
+/// Enabled modules in layout order, each listed once
+pub fn enabled_modules(config: &ModulesConfig) -> Vec<&'static dyn Module> {
+    let mut modules: Vec<&'static dyn Module> = Vec::new();
+    for entry in layout(config) {
+        if let LayoutEntry::Module(module) = entry
+            && !modules.iter().any(|known| known.name() == module.name())
+        {
+            modules.push(module);
+        }
+    }
+    modules
+}
+
+pub struct UserAtHost;
+
+impl Module for UserAtHost {
+    fn name(&self) -> &'static str { "user_at_host" }
+    fn default_label(&self) -> &'static str { "Login" }
+    fn enabled_by_default(&self) -> bool { true }
+    fn default_format(&self) -> &'static str { "{user}@{host}" }
+    fn collect(&self, _ctx: &CollectContext) -> Vec<Fields> {
+        vec![SystemInfo::get_user_at_host_info()]
+    }
+}
+
+pub struct User;
+
+impl Module for User {
+    fn name(&self) -> &'static str { "user" }
+    fn default_label(&self) -> &'static str { "User" }
+    fn enabled_by_default(&self) -> bool { true }
+    fn collect(&self, _ctx: &CollectContext) -> Vec<Fields> {
+        vec![Fields::value(Value::text_or(SystemInfo::get_user_info(), "user not found"))]
+    }
+}
+
+pub struct Hostname;
+
+impl Module for Hostname {
+    fn name(&self) -> &'static str { "hostname" }
+    fn default_label(&self) -> &'static str { "Hostname" }
+    fn enabled_by_default(&self) -> bool { true }
+    fn collect(&self, _ctx: &CollectContext) -> Vec<Fields> {
+        vec![Fields::value(Value::text_or(SystemInfo::get_hostname_info(), "hostname not set"))]
+    }
+}
+
+pub struct Os;
+
+impl Module for Os {
+    fn name(&self) -> &'static str { "os" }
+    fn default_label(&self) -> &'static str { "OS" }
+    fn enabled_by_default(&self) -> bool { true }
+    fn collect(&self, _ctx: &CollectContext) -> Vec<Fields> {
+        vec![Fields::value(Value::text_or(SystemInfo::get_os_info(), "no PRETTY_NAME in os-release"))]
+    }
+}
+
+pub struct Kernel;
+
+impl Module for Kernel {
+    fn name(&self) -> &'static str { "kernel" }
+    fn default_label(&self) -> &'static str { "Kernel" }
+    fn enabled_by_default(&self) -> bool { true }
+    fn collect(&self, _ctx: &CollectContext) -> Vec<Fields> {
+        vec![Fields::value(Value::text_or(SystemInfo::get_kernel_version(), "uname failed"))]
+    }
+}
+
+pub struct Linux;
+
+impl Module for Linux {
+    fn name(&self) -> &'static str { "linux" }
+    fn default_label(&self) -> &'static str { "Linux" }
+    fn enabled_by_default(&self) -> bool { true }
+    fn default_format(&self) -> &'static str { "{os} - {kernel}" }
+    fn collect(&self, _ctx: &CollectContext) -> Vec<Fields> {
+        vec![SystemInfo::get_linux_info()]
+    }
+}
+
+pub struct Uptime;
+
+impl Module for Uptime {
+    fn name(&self) -> &'static str { "uptime" }
+    fn default_label(&self) -> &'static str { "Uptime" }
+    fn enabled_by_default(&self) -> bool { true }
+    fn collect(&self, _ctx: &CollectContext) -> Vec<Fields> {
+        vec![Fields::value(Value::number(System::uptime() as f64, Unit::Seconds))]
+    }
+}
+
+pub struct OsAge;
+
+impl Module for OsAge {
+    fn name(&self) -> &'static str { "os_age" }
+    fn default_label(&self) -> &'static str { "OS Age" }
+    fn enabled_by_default(&self) -> bool { true }
+    fn default_format(&self) -> &'static str { "{days} days" }
+    fn cache_ttl(&self) -> Option<Duration> { Some(Duration::from_secs(60 * 60)) }
+    fn collect(&self, _ctx: &CollectContext) -> Vec<Fields> {
+        SystemInfo::get_os_age().into_iter().collect()
+    }
+}
+
+pub struct LastUpdate;
+
+impl Module for LastUpdate {
+    fn name(&self) -> &'static str { "last_update" }
+    fn default_label(&self) -> &'static str { "Updated" }
+    // dnf's history needs the dnf command
+    fn cost(&self) -> Cost { Cost::Slow }
+    fn default_format(&self) -> &'static str { "{days} days ago{?manager} ({manager}){?}{?reboot}, reboot pending: {reboot}{?}" }
+    fn collect(&self, _ctx: &CollectContext) -> Vec<Fields> {
+        SystemInfo::get_last_update().into_iter().collect()
+    }
+}
+
+pub struct Updates;
+
+impl Module for Updates {
+    fn name(&self) -> &'static str { "updates" }
+    fn default_label(&self) -> &'static str { "Updates" }
+    fn default_format(&self) -> &'static str {
+        "{?details}{total} ({details}){?}{?!details}up to date{?}, checked {age} ago"
+    }
+    fn collect(&self, ctx: &CollectContext) -> Vec<Fields> {
+        SystemInfo::get_pending_updates(&ctx.config.update_check).into_iter().collect()
+    }
+}
+
+// Package databases of the native managers; any install or removal touches one of these
+const NATIVE_PACKAGE_DATABASES: &[&str] = &[
+    "/var/lib/pacman/local",
+    "/var/lib/dpkg/status",
+    // apt-mark only changes this, which the manual/auto breakdown reads
+    "/var/lib/apt/extended_states",
+    "/var/lib/rpm",
+    "/var/db/xbps",
+    "/var/db/pkg",
+    "/lib/apk/db/installed",
+];
+
+// Where nix profiles and snaps record what they installed; Homebrew's are
+// derived from `packages::brew_prefixes`
+const ADDITIONAL_PACKAGE_ROOTS: &[&str] = &["/nix/var/nix/profiles", "~/.local/state/nix/profiles", "/snap"];
+
+const FLATPAK_INSTALLATIONS: &[&str] = &["/var/lib/flatpak/app", "~/.local/share/flatpak/app"];
+
+pub struct Packages;
+
+impl Module for Packages {
+    fn name(&self) -> &'static str { "packages" }
+    fn default_label(&self) -> &'static str { "Packages" }
+    fn multiple_entries(&self) -> bool { true }
+    fn cost(&self) -> Cost { Cost::Slow }
+    fn default_format(&self) -> &'static str { "{count} ({manager}{?details}: {details}{?})" }
+    fn cache_ttl(&self) -> Option<Duration> { Some(Duration::from_secs(24 * 60 * 60)) }
+    fn cache_inputs(&self) -> Vec<PathBuf> { paths(NATIVE_PACKAGE_DATABASES) }
+    fn entry_separator(&self) -> &'static str { ", " }
+    fn cache_config(&self, config: &ModulesConfig) -> String { json(&config.package_counts) }
+    fn collect(&self, ctx: &CollectContext) -> Vec<Fields> {
+        SystemInfo::get_package_count(&ctx.config.package_counts)
+    }
+}
+
+pub struct FlatpakPackages;
+
+impl Module for FlatpakPackages {
+    fn name(&self) -> &'static str { "flatpak_packages" }
+    fn default_label(&self) -> &'static str { "Flatpak" }
+    fn cost(&self) -> Cost { Cost::Slow }
+    fn default_format(&self) -> &'static str { "{count} ({manager})" }
+    fn cache_ttl(&self) -> Option<Duration> { Some(Duration::from_secs(24 * 60 * 60)) }
+    fn cache_inputs(&self) -> Vec<PathBuf> { paths(FLATPAK_INSTALLATIONS) }
+    fn collect(&self, _ctx: &CollectContext) -> Vec<Fields> {
+        SystemInfo::get_flatpak_packages().into_iter().collect()
+    }
+}
+
+pub struct PackagesCombined;
+
+impl Module for PackagesCombined {
+    fn name(&self) -> &'static str { "packages_combined" }
+    fn default_label(&self) -> &'static str { "Packages" }
+    fn multiple_entries(&self) -> bool { true }
+    fn display_name_key(&self) -> &'static str { "packages" }
+    fn enabled_by_default(&self) -> bool { true }
+    fn cost(&self) -> Cost { Cost::Slow }
+    fn default_format(&self) -> &'static str { "{count} ({manager}{?details}: {details}{?})" }
+    fn entry_separator(&self) -> &'static str { ", " }
+    fn cache_ttl(&self) -> Option<Duration> { Some(Duration::from_secs(24 * 60 * 60)) }
+    fn cache_inputs(&self) -> Vec<PathBuf> {
+        let mut inputs = paths(&[NATIVE_PACKAGE_DATABASES, ADDITIONAL_PACKAGE_ROOTS, FLATPAK_INSTALLATIONS].concat());
+        for prefix in packages::brew_prefixes() {
+            inputs.extend([prefix.join("Cellar"), prefix.join("Caskroom")]);
+        }
+        inputs
+    }
+    fn cache_config(&self, config: &ModulesConfig) -> String { json(&config.package_counts) }
+    fn collect(&self, ctx: &CollectContext) -> Vec<Fields> {
+        SystemInfo::get_combined_packages(&ctx.config.package_counts)
+    }
+}
+
+pub struct Shell;
+
+impl Module for Shell {
+    fn name(&self) -> &'static str { "shell" }
+    fn default_label(&self) -> &'static str { "Shell" }
+    fn enabled_by_default(&self) -> bool { true }
+    fn cost(&self) -> Cost { Cost::Slow }
+    fn cache_config(&self, config: &ModulesConfig) -> String { config.show_versions.to_string() }
+    fn collect(&self, ctx: &CollectContext) -> Vec<Fields> {
+        let shell = if ctx.show_versions {
+            SystemInfo::get_shell_with_version()
+        } else {
+            SystemInfo::get_shell()
+        };
+        vec![Fields::value(Value::text_or(shell, "SHELL not set"))]
+    }
+}
+
+pub struct Terminal;
+
+impl Module for Terminal {
+    fn name(&self) -> &'static str { "terminal" }
+    fn default_label(&self) -> &'static str { "Terminal" }
+    fn enabled_by_default(&self) -> bool { true }
+    fn cost(&self) -> Cost { Cost::Slow }
+    fn cache_config(&self, config: &ModulesConfig) -> String { config.show_versions.to_string() }
+    fn collect(&self, ctx: &CollectContext) -> Vec<Fields> {
+        let terminal = if ctx.show_versions {
+            SystemInfo::get_terminal_with_version()
+        } else {
+            SystemInfo::get_terminal()
+        };
+        vec![Fields::value(Value::text_or(terminal, "terminal not detected"))]
+    }
+}
+
+pub struct TerminalShellCombined;
+
+impl Module for TerminalShellCombined {
+    fn name(&self) -> &'static str { "terminal_shell_combined" }
+    fn default_label(&self) -> &'static str { "Terminal & Shell" }
+    fn multiple_entries(&self) -> bool { true }
+    fn cost(&self) -> Cost { Cost::Slow }
+    fn cache_config(&self, config: &ModulesConfig) -> String { config.show_versions.to_string() }
+    fn collect(&self, ctx: &CollectContext) -> Vec<Fields> {
+        SystemInfo::get_terminal_shell_combined(ctx.show_versions)
+    }
+}
+
+pub struct Multiplexer;
+
+impl Module for Multiplexer {
+    fn name(&self) -> &'static str { "multiplexer" }
+    fn default_label(&self) -> &'static str { "Multiplexer" }
+    fn collect(&self, _ctx: &CollectContext) -> Vec<Fields> {
+        SystemInfo::get_multiplexer().map(Fields::value).into_iter().collect()
+    }
+}
+
+pub struct Resolution;
+
+impl Module for Resolution {
+    fn name(&self) -> &'static str { "resolution" }
+    fn default_label(&self) -> &'static str { "Resolution" }
+    fn enabled_by_default(&self) -> bool { true }
+    fn cost(&self) -> Cost { Cost::Slow }
+    fn collect(&self, _ctx: &CollectContext) -> Vec<Fields> {
+        vec![Fields::value(Value::text_or(SystemInfo::get_resolution(), "no display found"))]
+    }
+}
+
+pub struct De;
+
+impl Module for De {
+    fn name(&self) -> &'static str { "de" }
+    fn default_label(&self) -> &'static str { "DE" }
+    fn enabled_by_default(&self) -> bool { true }
+    fn collect(&self, _ctx: &CollectContext) -> Vec<Fields> {
+        vec![Fields::value(Value::text_or(SystemInfo::get_desktop_environment(), "no desktop session"))]
+    }
+}
+
+pub struct Wm;
+
+impl Module for Wm {
+    fn name(&self) -> &'static str { "wm" }
+    fn default_label(&self) -> &'static str { "WM" }
+    fn enabled_by_default(&self) -> bool { true }
+    fn cost(&self) -> Cost { Cost::Slow }
+    fn collect(&self, _ctx: &CollectContext) -> Vec<Fields> {
+        vec![Fields::value(Value::text_or(SystemInfo::get_window_manager(), "window manager not detected"))]
+    }
+}
+
+pub struct Theme;
+
+impl Module for Theme {
+    fn name(&self) -> &'static str { "theme" }
+    fn default_label(&self) -> &'static str { "Theme" }
+    fn cost(&self) -> Cost { Cost::Slow }
+    fn collect(&self, _ctx: &CollectContext) -> Vec<Fields> {
+        vec![Fields::value(Value::text_or(SystemInfo::get_theme(), "theme not detected"))]
+    }
+}
+
+pub struct Icons;
+
+impl Module for Icons {
+    fn name(&self) -> &'static str { "icons" }
+    fn default_label(&self) -> &'static str { "Icons" }
+    fn cost(&self) -> Cost { Cost::Slow }
+    fn collect(&self, _ctx: &CollectContext) -> Vec<Fields> {
+        vec![Fields::value(Value::text_or(SystemInfo::get_icons(), "icon theme not detected"))]
+    }
+}
+
+pub struct Font;
+
+impl Module for Font {
+    fn name(&self) -> &'static str { "font" }
+    fn default_label(&self) -> &'static str { "Font" }
+    fn enabled_by_default(&self) -> bool { true }
+    fn cost(&self) -> Cost { Cost::Slow }
+    fn collect(&self, _ctx: &CollectContext) -> Vec<Fields> {
+        vec![Fields::value(SystemInfo::get_font_info())]
+    }
+}
+
+pub struct Locale;
+
+impl Module for Locale {
+    fn name(&self) -> &'static str { "locale" }
+    fn default_label(&self) -> &'static str { "Locale" }
+    fn collect(&self, _ctx: &CollectContext) -> Vec<Fields> {
+        vec![Fields::value(Value::text_or(SystemInfo::get_locale(), "locale not set"))]
+    }
+}
+
+pub struct Cpu;
+
+impl Module for Cpu {
+    fn name(&self) -> &'static str { "cpu" }
+    fn default_label(&self) -> &'static str { "CPU" }
+    fn enabled_by_default(&self) -> bool { true }
+    fn dependencies(&self) -> &'static [Dependency] { &[Dependency::Cpu] }
+    fn default_format(&self) -> &'static str { "{name}{?cores} ({cores} cores){?}" }
+    fn collect(&self, ctx: &CollectContext) -> Vec<Fields> {
+        vec![SystemInfo::get_cpu_info(ctx.sys)]
+    }
+}
+
+pub struct CpuTemp;
+
+impl Module for CpuTemp {
+    fn name(&self) -> &'static str { "cpu_temp" }
+    fn default_label(&self) -> &'static str { "CPU Temp" }
+    fn enabled_by_default(&self) -> bool { true }
+    fn cost(&self) -> Cost { Cost::Slow }
+    fn cache_config(&self, config: &ModulesConfig) -> String { json(&config.temperature) }
+    fn collect(&self, ctx: &CollectContext) -> Vec<Fields> {
+        let temp = SystemInfo::get_cpu_temperature(&ctx.config.temperature)
+            .map_or_else(|| Value::unavailable("no CPU sensor found"), |temp| Value::number(temp, Unit::Celsius));
+        vec![Fields::value(temp)]
+    }
+}
+
+pub struct Gpu;
+
+impl Module for Gpu {
+    fn name(&self) -> &'static str { "gpu" }
+    fn default_label(&self) -> &'static str { "GPU" }
+    fn multiple_entries(&self) -> bool { true }
+    fn enabled_by_default(&self) -> bool { true }
+    fn cost(&self) -> Cost { Cost::Slow }
+    fn default_format(&self) -> &'static str {
+        "{name}{?vram_total} ({?vram_used}{vram_used} / {?}{vram_total}){?}{?driver} [{driver}]{?}{?primary} (primary){?}"
+    }
+    fn entry_separator(&self) -> &'static str { "\n" }
+    fn collect(&self, ctx: &CollectContext) -> Vec<Fields> {
+        SystemInfo::get_gpu_info(&ctx.config.cache)
+    }
+}
+
+pub struct GpuTemp;
+
+impl Module for GpuTemp {
+    fn name(&self) -> &'static str { "gpu_temp" }
+    fn default_label(&self) -> &'static str { "GPU Temp" }
+    fn enabled_by_default(&self) -> bool { true }
+    fn cost(&self) -> Cost { Cost::Slow }
+    fn cache_config(&self, config: &ModulesConfig) -> String { json(&config.temperature) }
+    fn collect(&self, ctx: &CollectContext) -> Vec<Fields> {
+        let temp = SystemInfo::get_gpu_temperature(&ctx.config.temperature)
+            .map_or_else(|| Value::unavailable("no GPU sensor found"), |temp| Value::number(temp, Unit::Celsius));
+        vec![Fields::value(temp)]
+    }
+}
+
+pub struct GpuDriver;
+
+impl Module for GpuDriver {
+    fn name(&self) -> &'static str { "gpu_driver" }
+    fn default_label(&self) -> &'static str { "GPU Driver" }
+    fn multiple_entries(&self) -> bool { true }
+    fn enabled_by_default(&self) -> bool { true }
+    fn default_format(&self) -> &'static str { "{name} ({type}){?version} {version}{?}" }
+    fn collect(&self, ctx: &CollectContext) -> Vec<Fields> {
+        let drivers = SystemInfo::get_gpu_driver_info(&ctx.config.cache);
+        if drivers.is_empty() {
+            return vec![Fields::value(Value::unavailable("driver not detected"))];
+        }
+        drivers
+    }
+}
+
+pub struct TempCombined;
+
+impl Module for TempCombined {
+    fn name(&self) -> &'static str { "temp_combined" }
+    fn default_label(&self) -> &'static str { "Temperatures" }
+    fn multiple_entries(&self) -> bool { true }
+    fn cost(&self) -> Cost { Cost::Slow }
+    fn default_format(&self) -> &'static str { "{?label}{label} {?}{temp}" }
+    fn cache_config(&self, config: &ModulesConfig) -> String { json(&config.temperature) }
+    fn collect(&self, ctx: &CollectContext) -> Vec<Fields> {
+        SystemInfo::get_temp_combined(&ctx.config.temperature)
+    }
+}
+
+pub struct Sensors;
+
+impl Module for Sensors {
+    fn name(&self) -> &'static str { "sensors" }
+    fn default_label(&self) -> &'static str { "Sensors" }
+    fn multiple_entries(&self) -> bool { true }
+    fn default_format(&self) -> &'static str {
+        "{name:<20} {temp:>7}{?crit} (crit {crit}){?}{?!crit}{?max} (max {max}){?}{?}"
+    }
+    fn entry_separator(&self) -> &'static str { "\n" }
+    fn entry_color(&self, fields: &Fields) -> Option<&'static str> {
+        let temp = fields.get("temp")?.as_f64()?;
+        let limit = fields.get("crit").or_else(|| fields.get("max"))?.as_f64()?;
+        if limit <= 25.0 {
+            return None;
+        }
+        // Share of the way from room temperature to the limit
+        let closeness = (temp - 25.0) / (limit - 25.0);
+        Some(if closeness >= 0.9 {
+            "bright_red"
+        } else if closeness >= 0.7 {
+            "bright_yellow"
+        } else {
+            "bright_green"
+        })
+    }
+    fn collect(&self, _ctx: &CollectContext) -> Vec<Fields> {
+        SystemInfo::get_all_sensors()
+    }
+}
+
+pub struct FansPower;
+
+impl Module for FansPower {
+    fn name(&self) -> &'static str { "fans_power" }
+    fn default_label(&self) -> &'static str { "Fans & Power" }
+    fn multiple_entries(&self) -> bool { true }
+    // The system entry has no value while the battery isn't discharging
+    fn default_format(&self) -> &'static str { "{?value}{label} {value}{?}" }
+    fn collect(&self, _ctx: &CollectContext) -> Vec<Fields> {
+        SystemInfo::get_fans_power()
+    }
+}
+
+pub struct Memory;
+
+impl Module for Memory {
+    fn name(&self) -> &'static str { "memory" }
+    fn default_label(&self) -> &'static str { "Memory" }
+    fn enabled_by_default(&self) -> bool { true }
+    fn dependencies(&self) -> &'static [Dependency] { &[Dependency::Memory] }
+    fn default_format(&self) -> &'static str { "{used} / {total} ({percent})" }
+    fn collect(&self, ctx: &CollectContext) -> Vec<Fields> {
+        vec![SystemInfo::get_memory_info(ctx.sys)]
+    }
+}
+
+pub struct Battery;
+
+impl Module for Battery {
+    fn name(&self) -> &'static str { "battery" }
+    fn default_label(&self) -> &'static str { "Battery" }
+    fn enabled_by_default(&self) -> bool { true }
+    fn default_format(&self) -> &'static str { "{percent} ({status})" }
+    fn collect(&self, _ctx: &CollectContext) -> Vec<Fields> {
+        SystemInfo::get_battery_info().into_iter().collect()
+    }
+}
+
+pub struct Network;
+
+impl Module for Network {
+    fn name(&self) -> &'static str { "network" }
+    fn default_label(&self) -> &'static str { "Network" }
+    fn enabled_by_default(&self) -> bool { true }
+    fn default_format(&self) -> &'static str { "{?address}{address} ({interface}){?}{?!address}{interface}{?}" }
+    fn collect(&self, _ctx: &CollectContext) -> Vec<Fields> {
+        vec![SystemInfo::get_network_info()]
+    }
+}
+
+pub struct Interfaces;
+
+impl Module for Interfaces {
+    fn name(&self) -> &'static str { "interfaces" }
+    fn default_label(&self) -> &'static str { "Interfaces" }
+    fn multiple_entries(&self) -> bool { true }
+    // Interfaces first, then the DNS entry
+    fn default_format(&self) -> &'static str {
+        "{?interface}{interface:<12} {kind:<10}{?address} {address}{?}{?default} (default route){?}{?}{?dns}DNS {dns}{?search} (search {search}){?}{?}"
+    }
+    fn entry_separator(&self) -> &'static str { "\n" }
+    fn collect(&self, _ctx: &CollectContext) -> Vec<Fields> {
+        SystemInfo::get_network_interfaces()
+    }
+}
+
+pub struct PublicIp;
+
+impl Module for PublicIp {
+    fn name(&self) -> &'static str { "public_ip" }
+    fn default_label(&self) -> &'static str { "Public IP" }
+    fn cost(&self) -> Cost { Cost::Slow }
+    fn default_format(&self) -> &'static str { "{address}{?isp} ({isp}){?}" }
+    fn cache_ttl(&self) -> Option<Duration> { Some(Duration::from_secs(60 * 60)) }
+    fn cache_inputs(&self) -> Vec<PathBuf> { vec![PathBuf::from("/etc/resolv.conf")] }
+    fn cache_config(&self, config: &ModulesConfig) -> String { json(&config.public_ip_lookup) }
+    fn collect(&self, ctx: &CollectContext) -> Vec<Fields> {
+        vec![SystemInfo::get_public_ip_info(&ctx.config.public_ip_lookup)]
+    }
+}
+
+pub struct Disk;
+
+impl Module for Disk {
+    fn name(&self) -> &'static str { "disk" }
+    fn default_label(&self) -> &'static str { "Disk" }
+    fn multiple_entries(&self) -> bool { true }
+    fn enabled_by_default(&self) -> bool { true }
+    fn cost(&self) -> Cost { Cost::Slow }
+    fn default_format(&self) -> &'static str { "{mount}{?total} {used} / {total} ({percent}){?}{?fs} [{fs}]{?}" }
+    fn collect(&self, _ctx: &CollectContext) -> Vec<Fields> {
+        SystemInfo::get_disk_info()
+    }
+}
+
+pub struct Dysk;
+
+impl Module for Dysk {
+    fn name(&self) -> &'static str { "dysk" }
+    fn default_label(&self) -> &'static str { "Drives" }
+    fn multiple_entries(&self) -> bool { true }
+    fn enabled_by_default(&self) -> bool { true }
+    fn cost(&self) -> Cost { Cost::Slow }
+    fn default_format(&self) -> &'static str { "{?bar}{bar} {percent:>4} {?}{device} {?total}{used:>4}/{total:<4} {?}[{fs}] {mount}" }
+    fn entry_separator(&self) -> &'static str { "\n" }
+    fn collect(&self, _ctx: &CollectContext) -> Vec<Fields> {
+        SystemInfo::get_dysk_info()
+    }
+}
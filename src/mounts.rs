@@ -0,0 +1,196 @@
+use std::ffi::CString;
+use std::mem::MaybeUninit;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::sync::OnceLock;
+use crate::sysroot;
+
+/// One line of `/proc/self/mountinfo`
+#[derive(Debug, Clone)]
+pub struct Mount {
+    /// `major:minor` of the mounted filesystem; equal for bind mounts of the same one
+    pub device_id: String,
+    pub mount_point: String,
+    pub fs_type: String,
+    /// Device or other source, e.g. `/dev/nvme0n1p2` or `tmpfs`
+    pub source: String,
+}
+
+/// Space on a mounted filesystem in bytes, as `df` reports it
+#[derive(Debug, Clone, Copy)]
+pub struct Usage {
+    pub total: u64,
+    pub used: u64,
+    /// Free space available to unprivileged users
+    pub available: u64,
+    /// Free space only root may use (e.g. the 5% ext4 keeps back)
+    pub reserved: u64,
+}
+
+impl Usage {
+    /// Used share of the space a normal user can fill, which excludes reserved blocks
+    /// the same way `df` does, so a "full" disk shows 100% even though root could write more
+    pub fn percent(&self) -> f64 {
+        let usable = self.used + self.available;
+        if usable == 0 {
+            0.0
+        } else {
+            self.used as f64 / usable as f64 * 100.0
+        }
+    }
+}
+
+impl Mount {
+    // "36 35 98:0 /mnt1 /mnt/parent rw,noatime master:1 - ext3 /dev/root rw,errors=continue";
+    // a variable number of optional fields precedes the "-" separator
+    fn parse(line: &str) -> Option<Self> {
+        let (mount, filesystem) = line.split_once(" - ")?;
+        let mut mount = mount.split(' ');
+        let device_id = mount.nth(2)?.to_string();
+        let mount_point = unescape(mount.nth(1)?);
+        let mut filesystem = filesystem.split(' ');
+        let fs_type = unescape(filesystem.next()?);
+        let source = unescape(filesystem.next()?);
+
+        Some(Self {
+            device_id,
+            mount_point,
+            fs_type,
+            source,
+        })
+    }
+
+    /// Device name without `/dev/`, e.g. `nvme0n1p2`
+    pub fn device(&self) -> &str {
+        self.source.strip_prefix("/dev/").unwrap_or(&self.source)
+    }
+
+    /// Current space usage via statvfs
+    pub fn usage(&self) -> Result<Usage, Unmeasured> {
+        // The mount points of a snapshot are plain directories on the host
+        if sysroot::is_set() {
+            return Err(Unmeasured::Sysroot);
+        }
+        statvfs(Path::new(&self.mount_point)).ok_or(Unmeasured::Inaccessible)
+    }
+}
+
+/// Why a mount's space usage is unknown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unmeasured {
+    /// statvfs would measure the host filesystem holding the snapshot
+    Sysroot,
+    /// statvfs failed, e.g. on another user's FUSE mount
+    Inaccessible,
+}
+
+impl Unmeasured {
+    pub fn reason(self) -> &'static str {
+        match self {
+            Unmeasured::Sysroot => "not measured for a sysroot",
+            Unmeasured::Inaccessible => "filesystem not accessible",
+        }
+    }
+}
+
+// The kernel writes space, tab, newline and backslash as octal escapes (`\040`)
+fn unescape(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let octal = bytes
+            .get(index + 1..index + 4)
+            .filter(|digits| bytes[index] == b'\\' && digits.iter().all(|digit| (b'0'..=b'7').contains(digit)))
+            .and_then(|digits| u8::from_str_radix(std::str::from_utf8(digits).ok()?, 8).ok());
+        match octal {
+            Some(byte) => {
+                unescaped.push(byte);
+                index += 4;
+            }
+            None => {
+                unescaped.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&unescaped).into_owned()
+}
+
+fn statvfs(path: &Path) -> Option<Usage> {
+    let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    let mut stat = MaybeUninit::<libc::statvfs>::uninit();
+    // SAFETY: `path` is NUL-terminated and `stat` is only read after statvfs succeeded
+    let stat = unsafe {
+        if libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) != 0 {
+            return None;
+        }
+        stat.assume_init()
+    };
+
+    let fragment = stat.f_frsize as u64;
+    let total = stat.f_blocks as u64 * fragment;
+    let free = stat.f_bfree as u64 * fragment;
+    let available = (stat.f_bavail as u64 * fragment).min(free);
+    Some(Usage {
+        total,
+        used: total.saturating_sub(free),
+        available,
+        reserved: free - available,
+    })
+}
+
+/// All mounts in the order they were mounted, read once per run
+pub fn table() -> &'static [Mount] {
+    static TABLE: OnceLock<Vec<Mount>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        sysroot::read_to_string("/proc/self/mountinfo")
+            .map(|content| content.lines().filter_map(Mount::parse).collect())
+            .unwrap_or_default()
+    })
+}
+
+/// The mount mounted exactly at `mount_point`; the topmost one if several are stacked
+pub fn at(mount_point: &str) -> Option<&'static Mount> {
+    table().iter().rev().find(|mount| mount.mount_point == mount_point)
+}
+
+/// The mount a path lives on: the longest mount point that is a prefix of it
+pub fn containing(path: &str) -> Option<&'static Mount> {
+    let path = Path::new(path);
+    // max_by_key keeps the last of equal lengths, i.e. the topmost mount
+    table()
+        .iter()
+        .filter(|mount| path.starts_with(&mount.mount_point))
+        .max_by_key(|mount| mount.mount_point.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unescapes_octal_sequences() {
+        assert_eq!(unescape(r"/mnt/my\040disk"), "/mnt/my disk");
+        assert_eq!(unescape(r"tab\011back\134slash"), "tab\tback\\slash");
+        // Not an escape: too few or non-octal digits
+        assert_eq!(unescape(r"a\04"), r"a\04");
+        assert_eq!(unescape(r"a\089"), r"a\089");
+    }
+
+    #[test]
+    fn parses_mountinfo_lines() {
+        let mount = Mount::parse("36 35 98:0 /mnt1 /mnt/parent rw,noatime master:1 - ext3 /dev/root rw,errors=continue").unwrap();
+        assert_eq!(mount.device_id, "98:0");
+        assert_eq!(mount.mount_point, "/mnt/parent");
+        assert_eq!(mount.fs_type, "ext3");
+        assert_eq!(mount.device(), "root");
+
+        // No optional fields before the separator, and an escaped mount point
+        let mount = Mount::parse(r"23 22 8:1 / /mnt/my\040disk rw - vfat /dev/sdb1 rw").unwrap();
+        assert_eq!(mount.mount_point, "/mnt/my disk");
+        assert_eq!(mount.source, "/dev/sdb1");
+
+        assert!(Mount::parse("23 22 8:1 / /mnt rw").is_none());
+    }
+}
@@ -0,0 +1,456 @@
+use std::ffi::CStr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+use crate::sysroot;
+
+// Route flags from <linux/route.h> and <linux/ipv6_route.h>
+const RTF_UP: u32 = 0x0001;
+const RTF_REJECT: u32 = 0x0200;
+
+/// A default route from the kernel routing table
+#[derive(Debug, Clone)]
+pub struct Route {
+    pub interface: String,
+    /// `None` for point-to-point links and VPNs that route without a next hop
+    pub gateway: Option<IpAddr>,
+    pub metric: u32,
+}
+
+/// An address assigned to an interface
+#[derive(Debug, Clone)]
+pub struct Address {
+    pub interface: String,
+    pub address: IpAddr,
+}
+
+impl Address {
+    /// Reachable beyond the local link: not loopback or link-local (`169.254/16`, `fe80::/10`)
+    pub fn is_global(&self) -> bool {
+        match self.address {
+            IpAddr::V4(address) => !address.is_loopback() && !address.is_link_local(),
+            IpAddr::V6(address) => !address.is_loopback() && !address.is_unicast_link_local(),
+        }
+    }
+}
+
+/// IPv4 default route with the lowest metric, from `/proc/net/route`
+pub fn default_route_v4() -> Option<Route> {
+    parse_route_v4(&sysroot::read_to_string("/proc/net/route").ok()?)
+}
+
+fn parse_route_v4(table: &str) -> Option<Route> {
+    // "eth0  00000000  010200C0  0003  0  0  100  00000000 ..."; addresses are
+    // 32-bit words printed in host byte order
+    let address = |hex: &str| u32::from_str_radix(hex, 16).ok().map(|word| Ipv4Addr::from(word.to_ne_bytes()));
+    table
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let flags = u32::from_str_radix(fields.get(3)?, 16).ok()?;
+            let is_default = *fields.get(1)? == "00000000" && *fields.get(7)? == "00000000";
+            if !is_default || flags & RTF_UP == 0 || flags & RTF_REJECT != 0 {
+                return None;
+            }
+            let gateway = address(fields[2]).filter(|gateway| !gateway.is_unspecified());
+            Some(Route {
+                interface: fields[0].to_string(),
+                gateway: gateway.map(IpAddr::V4),
+                metric: fields.get(6)?.parse().ok()?,
+            })
+        })
+        .min_by_key(|route| route.metric)
+}
+
+/// IPv6 default route with the lowest metric, from `/proc/net/ipv6_route`
+pub fn default_route_v6() -> Option<Route> {
+    parse_route_v6(&sysroot::read_to_string("/proc/net/ipv6_route").ok()?)
+}
+
+fn parse_route_v6(table: &str) -> Option<Route> {
+    // destination, prefix length, source, source prefix length, next hop, metric,
+    // refcount, use count, flags, interface; addresses as 32 hex digits
+    let address = |hex: &str| u128::from_str_radix(hex, 16).ok().map(Ipv6Addr::from);
+    table
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 {
+                return None;
+            }
+            let flags = u32::from_str_radix(fields[8], 16).ok()?;
+            let is_default = address(fields[0])?.is_unspecified() && fields[1] == "00";
+            // The kernel keeps a rejecting default route on lo when there is no real one
+            if !is_default || flags & RTF_UP == 0 || flags & RTF_REJECT != 0 || fields[9] == "lo" {
+                return None;
+            }
+            let gateway = address(fields[4]).filter(|gateway| !gateway.is_unspecified());
+            Some(Route {
+                interface: fields[9].to_string(),
+                gateway: gateway.map(IpAddr::V6),
+                metric: u32::from_str_radix(fields[5], 16).ok()?,
+            })
+        })
+        .min_by_key(|route| route.metric)
+}
+
+/// IPv4 and IPv6 addresses of all interfaces, in the kernel's order.
+///
+/// getifaddrs only knows about the running machine, so a sysroot is read from
+/// `/proc/net/fib_trie` and `/proc/net/if_inet6` instead.
+pub fn addresses() -> Vec<Address> {
+    if sysroot::is_set() {
+        return [addresses_v4_from_proc(), addresses_v6_from_proc()].concat();
+    }
+
+    let mut addresses = Vec::new();
+    let mut list: *mut libc::ifaddrs = std::ptr::null_mut();
+    // SAFETY: getifaddrs fills `list` on success; every node is only read until
+    // freeifaddrs, and the address structs are read according to their family
+    unsafe {
+        if libc::getifaddrs(&mut list) != 0 {
+            return addresses;
+        }
+        let mut node = list;
+        while let Some(entry) = node.as_ref() {
+            node = entry.ifa_next;
+            if entry.ifa_addr.is_null() || entry.ifa_name.is_null() {
+                continue;
+            }
+            let interface = CStr::from_ptr(entry.ifa_name).to_string_lossy().into_owned();
+            let address = match i32::from((*entry.ifa_addr).sa_family) {
+                libc::AF_INET => {
+                    let address = &*(entry.ifa_addr as *const libc::sockaddr_in);
+                    IpAddr::V4(Ipv4Addr::from(u32::from_be(address.sin_addr.s_addr)))
+                }
+                libc::AF_INET6 => {
+                    let address = &*(entry.ifa_addr as *const libc::sockaddr_in6);
+                    IpAddr::V6(Ipv6Addr::from(address.sin6_addr.s6_addr))
+                }
+                // AF_PACKET entries carry the MAC address
+                _ => continue,
+            };
+            addresses.push(Address { interface, address });
+        }
+        libc::freeifaddrs(list);
+    }
+    addresses
+}
+
+// "fe80000000000000021122fffe334455 02 40 20 80   enp5s0": address, ifindex,
+// prefix length, scope, flags and interface
+fn addresses_v6_from_proc() -> Vec<Address> {
+    let table = sysroot::read_to_string("/proc/net/if_inet6").unwrap_or_default();
+    parse_if_inet6(&table)
+}
+
+fn parse_if_inet6(table: &str) -> Vec<Address> {
+    table
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let address = Ipv6Addr::from(u128::from_str_radix(fields.first()?, 16).ok()?);
+            Some(Address { interface: fields.get(5)?.to_string(), address: IpAddr::V6(address) })
+        })
+        .collect()
+}
+
+// The routing trie lists local addresses as "/32 host LOCAL" leaves, without
+// their interface; that comes from the subnet route covering the address
+fn addresses_v4_from_proc() -> Vec<Address> {
+    let trie = sysroot::read_to_string("/proc/net/fib_trie").unwrap_or_default();
+    let routes = sysroot::read_to_string("/proc/net/route").unwrap_or_default();
+    parse_fib_trie(&trie, &routes)
+}
+
+fn parse_fib_trie(trie: &str, routes: &str) -> Vec<Address> {
+    // Subnet routes from /proc/net/route as (interface, network, mask), in host byte order
+    let word = |hex: &str| u32::from_str_radix(hex, 16).ok().map(|word| u32::from_be_bytes(word.to_ne_bytes()));
+    let subnets: Vec<(&str, u32, u32)> = routes
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            Some((*fields.first()?, word(fields.get(1)?)?, word(fields.get(7)?)?))
+        })
+        .filter(|&(_, _, mask)| mask != 0)
+        .collect();
+
+    let mut addresses: Vec<Address> = Vec::new();
+    let mut leaf: Option<Ipv4Addr> = None;
+    for line in trie.lines() {
+        let line = line.trim_start();
+        if let Some(address) = line.strip_prefix("|-- ") {
+            leaf = address.trim().parse().ok();
+        } else if line.starts_with("/32 host LOCAL")
+            && let Some(address) = leaf
+            // Local addresses appear in both the main and the local table
+            && !addresses.iter().any(|known| known.address == IpAddr::V4(address))
+        {
+            let bits = u32::from(address);
+            let interface = if address.is_loopback() {
+                Some("lo")
+            } else {
+                subnets
+                    .iter()
+                    .filter(|&&(_, network, mask)| bits & mask == network)
+                    .max_by_key(|&&(_, _, mask)| mask.count_ones())
+                    .map(|&(interface, _, _)| interface)
+            };
+            if let Some(interface) = interface {
+                addresses.push(Address { interface: interface.to_string(), address: IpAddr::V4(address) });
+            }
+        }
+    }
+    addresses
+}
+
+/// MTU of an interface in bytes
+pub fn mtu(interface: &str) -> Option<u32> {
+    read_attribute(interface, "mtu")?.parse().ok()
+}
+
+/// Negotiated link speed in Mbit/s; wireless and disconnected interfaces report none
+pub fn speed(interface: &str) -> Option<u32> {
+    // Unknown speeds read as -1 (or fail with EINVAL while the link is down)
+    read_attribute(interface, "speed")?.parse::<i64>().ok().filter(|&speed| speed > 0).map(|speed| speed as u32)
+}
+
+fn read_attribute(interface: &str, attribute: &str) -> Option<String> {
+    let path = Path::new("/sys/class/net").join(interface).join(attribute);
+    Some(sysroot::read_to_string(path).ok()?.trim().to_string())
+}
+
+/// What kind of link an interface is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Ethernet,
+    Wifi,
+    WireGuard,
+    Tun,
+    Tap,
+    Bridge,
+    /// docker0, per-network `br-*` bridges and container veth pairs
+    Docker,
+    Loopback,
+    /// Anything else virtual (bonds, VLANs, macvlan, ...)
+    Virtual,
+}
+
+impl Kind {
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Ethernet => "ethernet",
+            Kind::Wifi => "wifi",
+            Kind::WireGuard => "wireguard",
+            Kind::Tun => "tun",
+            Kind::Tap => "tap",
+            Kind::Bridge => "bridge",
+            Kind::Docker => "docker",
+            Kind::Loopback => "loopback",
+            Kind::Virtual => "virtual",
+        }
+    }
+}
+
+/// A network interface from `/sys/class/net`
+#[derive(Debug, Clone)]
+pub struct Interface {
+    pub name: String,
+    pub kind: Kind,
+    /// Administratively up with a link that isn't down
+    pub up: bool,
+}
+
+impl Interface {
+    fn read(name: &str) -> Self {
+        let attribute = |attribute: &str| read_attribute(name, attribute);
+        let devtype = attribute("uevent").and_then(|uevent| {
+            uevent.lines().find_map(|line| line.strip_prefix("DEVTYPE=")).map(str::to_string)
+        });
+        // IFF_TAP in tun_flags tells the two apart
+        let tun_flags = attribute("tun_flags").and_then(|flags| u32::from_str_radix(flags.trim_start_matches("0x"), 16).ok());
+        let is_physical = sysroot::exists(Path::new("/sys/class/net").join(name).join("device"));
+
+        let kind = if attribute("type").as_deref() == Some("772") {
+            Kind::Loopback
+        } else if name == "docker0" || name.starts_with("br-") || name.starts_with("veth") {
+            Kind::Docker
+        } else if let Some(flags) = tun_flags {
+            if flags & 0x0002 != 0 { Kind::Tap } else { Kind::Tun }
+        } else {
+            match devtype.as_deref() {
+                Some("wlan") => Kind::Wifi,
+                Some("wireguard") => Kind::WireGuard,
+                Some("bridge") => Kind::Bridge,
+                _ if is_physical => Kind::Ethernet,
+                _ => Kind::Virtual,
+            }
+        };
+
+        // IFF_UP; tun and wireguard links report their operstate as "unknown"
+        let flags = attribute("flags").and_then(|flags| u32::from_str_radix(flags.trim_start_matches("0x"), 16).ok());
+        let up = flags.is_some_and(|flags| flags & 0x1 != 0)
+            && !matches!(attribute("operstate").as_deref(), Some("down") | Some("lowerlayerdown") | Some("notpresent"));
+
+        Self { name: name.to_string(), kind, up }
+    }
+}
+
+/// All interfaces in ifindex order
+pub fn interfaces() -> Vec<Interface> {
+    let mut names: Vec<(u32, String)> = sysroot::read_dir("/sys/class/net")
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?.to_string();
+            let index = read_attribute(&name, "ifindex").and_then(|index| index.parse().ok()).unwrap_or(u32::MAX);
+            Some((index, name))
+        })
+        .collect();
+    names.sort();
+    names.into_iter().map(|(_, name)| Interface::read(&name)).collect()
+}
+
+/// DNS configuration from resolv.conf
+#[derive(Debug, Clone, Default)]
+pub struct Resolver {
+    pub nameservers: Vec<String>,
+    pub search: Vec<String>,
+}
+
+impl Resolver {
+    fn parse(content: &str) -> Self {
+        let mut resolver = Self::default();
+        for line in content.lines() {
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("nameserver") => resolver.nameservers.extend(words.next().map(str::to_string)),
+                // The last of "search" and "domain" wins
+                Some("search") | Some("domain") => resolver.search = words.map(str::to_string).collect(),
+                _ => {}
+            }
+        }
+        resolver
+    }
+
+    /// Whether this is systemd-resolved's local stub rather than real servers
+    fn is_stub(&self) -> bool {
+        !self.nameservers.is_empty() && self.nameservers.iter().all(|server| server == "127.0.0.53" || server == "127.0.0.54")
+    }
+}
+
+/// Nameservers and search domains in use. When `/etc/resolv.conf` points at the
+/// systemd-resolved stub, the upstream servers it forwards to are reported instead.
+pub fn resolver() -> Option<Resolver> {
+    let resolver = Resolver::parse(&sysroot::read_to_string("/etc/resolv.conf").ok()?);
+    if resolver.is_stub()
+        && let Ok(content) = sysroot::read_to_string("/run/systemd/resolve/resolv.conf")
+    {
+        let upstream = Resolver::parse(&content);
+        if !upstream.nameservers.is_empty() {
+            return Some(upstream);
+        }
+    }
+    Some(resolver)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // As read on a little-endian machine, where the words print byte-swapped
+    const ROUTES: &str = "\
+Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT
+wlan0\t00000000\t0101A8C0\t0003\t0\t0\t600\t00000000\t0\t0\t0
+enp5s0\t00000000\t0100A8C0\t0003\t0\t0\t100\t00000000\t0\t0\t0
+enp5s0\t0000A8C0\t00000000\t0001\t0\t0\t100\t00FFFFFF\t0\t0\t0
+";
+
+    #[test]
+    fn picks_the_ipv4_default_route_with_the_lowest_metric() {
+        let route = parse_route_v4(ROUTES).unwrap();
+        assert_eq!(route.interface, "enp5s0");
+        assert_eq!(route.gateway, Some(IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1))));
+        assert_eq!(route.metric, 100);
+
+        // Point-to-point links route without a gateway; rejecting routes don't count
+        let table = "Iface\tDestination\tGateway\tFlags\tRefCnt\tUse\tMetric\tMask
+tun0\t00000000\t00000000\t0001\t0\t0\t50\t00000000
+eth0\t00000000\t0100A8C0\t0201\t0\t0\t10\t00000000
+";
+        let route = parse_route_v4(table).unwrap();
+        assert_eq!(route.interface, "tun0");
+        assert_eq!(route.gateway, None);
+    }
+
+    #[test]
+    fn parses_ipv6_routes() {
+        let table = "\
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 fe800000000000000000000000000001 00000400 00000001 00000000 00000003   enp5s0
+20010db8000000000000000000000000 40 00000000000000000000000000000000 00 00000000000000000000000000000000 00000100 00000001 00000000 00000001   enp5s0
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 00000000000000000000000000000000 ffffffff 00000001 00000000 00200200       lo
+";
+        let route = parse_route_v6(table).unwrap();
+        assert_eq!(route.interface, "enp5s0");
+        assert_eq!(route.gateway, Some("fe80::1".parse().unwrap()));
+        assert_eq!(route.metric, 0x400);
+
+        // Only the kernel's rejecting placeholder on lo
+        assert!(parse_route_v6(table.lines().last().unwrap()).is_none());
+    }
+
+    #[test]
+    fn reads_addresses_from_if_inet6() {
+        let table = "\
+00000000000000000000000000000001 01 80 10 80       lo
+20010db8000000000000000000000042 02 40 00 00   enp5s0
+";
+        let addresses = parse_if_inet6(table);
+        assert_eq!(addresses.len(), 2);
+        assert_eq!(addresses[1].interface, "enp5s0");
+        assert_eq!(addresses[1].address, "2001:db8::42".parse::<IpAddr>().unwrap());
+        assert!(!addresses[0].is_global());
+    }
+
+    #[test]
+    fn matches_fib_trie_addresses_to_subnet_routes() {
+        let trie = "\
+Main:
+  +-- 0.0.0.0/0 3 0 5
+     +-- 127.0.0.0/8 2 0 2
+        +-- 127.0.0.0/31 1 0 0
+           |-- 127.0.0.1
+              /32 host LOCAL
+     +-- 192.168.0.0/24 2 0 2
+        |-- 192.168.0.0
+           /24 link UNICAST
+        |-- 192.168.0.42
+           /32 host LOCAL
+        |-- 192.168.0.255
+           /32 link BROADCAST
+Local:
+  +-- 0.0.0.0/0 3 0 5
+     +-- 192.168.0.0/24 2 0 2
+        |-- 192.168.0.42
+           /32 host LOCAL
+";
+        let addresses: Vec<(String, String)> = parse_fib_trie(trie, ROUTES)
+            .into_iter()
+            .map(|address| (address.interface, address.address.to_string()))
+            .collect();
+        assert_eq!(
+            addresses,
+            [("lo".to_string(), "127.0.0.1".to_string()), ("enp5s0".to_string(), "192.168.0.42".to_string())]
+        );
+    }
+
+    #[test]
+    fn parses_resolv_conf() {
+        let resolver = Resolver::parse("# generated\nnameserver 127.0.0.53\noptions edns0\ndomain lan\nsearch corp.example.com lan\n");
+        assert_eq!(resolver.nameservers, ["127.0.0.53"]);
+        assert_eq!(resolver.search, ["corp.example.com", "lan"]);
+        assert!(resolver.is_stub());
+        assert!(!Resolver::parse("nameserver 127.0.0.53\nnameserver 1.1.1.1\n").is_stub());
+    }
+}
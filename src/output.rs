@@ -0,0 +1,138 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+use serde::ser::{SerializeMap, Serializer};
+use crate::config::Config;
+use crate::modules::enabled_modules;
+use crate::system_info::SystemInfo;
+use crate::value::{Fields, Value};
+
+/// How the collected information is printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Colored output with logo and MOTD
+    Text,
+    Json,
+    Yaml,
+    Toml,
+    /// Shell `KEY='value'` lines
+    Env,
+}
+
+/// One enabled module in machine-readable output
+#[derive(Serialize)]
+struct ModuleReport<'a> {
+    label: String,
+    /// Rendered with the module's format string, as shown in text output
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    /// Collection ran out of time; `entries` is empty
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    timed_out: bool,
+    entries: &'a [Fields],
+    /// The module can report several entries
+    #[serde(skip)]
+    indexed: bool,
+}
+
+/// Enabled modules keyed by name, in display order
+struct Report<'a>(Vec<(&'static str, ModuleReport<'a>)>);
+
+impl Serialize for Report<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (name, module) in &self.0 {
+            map.serialize_entry(name, module)?;
+        }
+        map.end()
+    }
+}
+
+impl<'a> Report<'a> {
+    fn new(system_info: &'a SystemInfo, config: &Config) -> Self {
+        let modules = &config.modules;
+        let reports = enabled_modules(modules)
+            .into_iter()
+            .map(|module| {
+                let entries = system_info
+                    .data
+                    .get(module.name())
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                let report = ModuleReport {
+                    label: modules.display_name(module),
+                    text: system_info.render(module, modules),
+                    timed_out: system_info.timed_out.contains(module.name()),
+                    entries,
+                    indexed: module.multiple_entries(),
+                };
+                (module.name(), report)
+            })
+            .collect();
+        Self(reports)
+    }
+}
+
+/// Print collected information in a machine-readable format, without colors or images
+pub fn print(system_info: &SystemInfo, config: &Config, format: OutputFormat) -> Result<()> {
+    let report = Report::new(system_info, config);
+
+    let output = match format {
+        // Handled by Display
+        OutputFormat::Text => return Ok(()),
+        OutputFormat::Json => serde_json::to_string_pretty(&report)? + "\n",
+        OutputFormat::Yaml => serde_yaml::to_string(&report)?,
+        OutputFormat::Toml => toml::to_string(&report)?,
+        OutputFormat::Env => env_lines(&report),
+    };
+
+    print!("{}", output);
+    Ok(())
+}
+
+// TERMGREET_<MODULE>='text' and TERMGREET_<MODULE>_LABEL describe the module;
+// its fields follow as TERMGREET_<MODULE>_FIELD_<NAME>. Modules that can have
+// several entries always number them, TERMGREET_DISK_0_MOUNT, with the count
+// in TERMGREET_DISK_ENTRIES, so the names don't depend on the machine.
+fn env_lines(report: &Report) -> String {
+    let mut lines = Vec::new();
+
+    for (name, module) in &report.0 {
+        let prefix = format!("TERMGREET_{}", name.to_uppercase());
+        lines.push(format!("{}_LABEL={}", prefix, shell_quote(&module.label)));
+        if let Some(text) = &module.text {
+            lines.push(format!("{}={}", prefix, shell_quote(text)));
+        }
+        if module.timed_out {
+            lines.push(format!("{}_TIMED_OUT=1", prefix));
+        }
+        if module.indexed {
+            lines.push(format!("{}_ENTRIES={}", prefix, module.entries.len()));
+        }
+
+        for (index, fields) in module.entries.iter().enumerate() {
+            let entry_prefix = if module.indexed {
+                format!("{}_{}", prefix, index)
+            } else {
+                format!("{}_FIELD", prefix)
+            };
+            for (field, value) in fields.iter() {
+                let raw = match value {
+                    Value::Text(text) => text.clone(),
+                    Value::Number { value, .. } => value.to_string(),
+                    // One item per line, for `while read` or `mapfile`
+                    Value::List(items) => items.iter().map(Value::to_string).collect::<Vec<_>>().join("\n"),
+                    Value::Unavailable { .. } => continue,
+                };
+                lines.push(format!("{}_{}={}", entry_prefix, field.to_uppercase(), shell_quote(&raw)));
+            }
+        }
+    }
+
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
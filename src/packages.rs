@@ -0,0 +1,343 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use crate::commands;
+use crate::sysroot;
+
+/// Installed packages of one package manager
+#[derive(Debug, Clone)]
+pub struct Count {
+    pub manager: &'static str,
+    pub count: usize,
+}
+
+// Package count, or `None` if the manager isn't in use
+type Counter = fn() -> Option<usize>;
+
+/// Packages of every distribution package manager that has a database here.
+/// Most are counted from their database files; rpm's and xbps's are binary or
+/// plist, so their tools are asked instead.
+pub fn native() -> Vec<Count> {
+    let counters: [(&str, Counter); 6] = [
+        ("dpkg", dpkg),
+        ("pacman", pacman),
+        ("apk", apk),
+        ("portage", portage),
+        ("rpm", rpm),
+        ("xbps", xbps),
+    ];
+    collect(&counters)
+}
+
+/// Packages managed outside the distribution: nix profiles, snaps and Homebrew
+pub fn additional() -> Vec<Count> {
+    let counters: [(&str, Counter); 4] = [
+        ("nix-system", nix_system),
+        ("nix-user", nix_user),
+        ("snap", snap),
+        ("brew", brew),
+    ];
+    collect(&counters)
+}
+
+fn collect(counters: &[(&'static str, Counter)]) -> Vec<Count> {
+    counters
+        .iter()
+        .filter_map(|&(manager, counter)| Some(Count { manager, count: counter()? }))
+        .filter(|count| count.count > 0)
+        .collect()
+}
+
+fn dpkg() -> Option<usize> {
+    Some(dpkg_installed()?.len())
+}
+
+// Installed packages in the status file as (name, architecture); on multiarch
+// systems libc6:amd64 and libc6:i386 are separate packages. Removed packages with
+// leftover config files ("Status: deinstall ok config-files") stay listed there too.
+fn dpkg_installed() -> Option<HashSet<(String, String)>> {
+    Some(parse_dpkg_status(&sysroot::read_to_string("/var/lib/dpkg/status").ok()?))
+}
+
+fn parse_dpkg_status(status: &str) -> HashSet<(String, String)> {
+    status
+        .split("\n\n")
+        .filter(|stanza| {
+            stanza
+                .lines()
+                .filter_map(|line| line.strip_prefix("Status:"))
+                .any(|status| status.split_whitespace().nth(2) == Some("installed"))
+        })
+        .filter_map(|stanza| {
+            let name = stanza_field(stanza, "Package:")?;
+            Some((name.to_string(), stanza_field(stanza, "Architecture:").unwrap_or_default().to_string()))
+        })
+        .collect()
+}
+
+/// Installed packages apt marked as automatically installed (pulled in as a
+/// dependency), from `/var/lib/apt/extended_states`
+pub fn apt_automatic() -> Option<usize> {
+    let installed = dpkg_installed()?;
+    let states = sysroot::read_to_string("/var/lib/apt/extended_states").ok()?;
+    Some(count_automatic(&installed, &states))
+}
+
+fn count_automatic(installed: &HashSet<(String, String)>, states: &str) -> usize {
+    let automatic = parse_auto_installed(states);
+    let is_automatic = |(name, architecture): &(String, String)| {
+        automatic.get(name.as_str()).is_some_and(|architectures| {
+            // apt records Architecture: all packages under the native architecture
+            architecture == "all" || architectures.iter().any(|marked| marked == architecture || marked.is_empty())
+        })
+    };
+    installed.iter().filter(|package| is_automatic(package)).count()
+}
+
+// Architectures of each package with "Auto-Installed: 1"; the file also lists
+// uninstalled ones. Entries written by old apt versions have no architecture.
+fn parse_auto_installed(states: &str) -> HashMap<&str, Vec<&str>> {
+    let mut automatic: HashMap<&str, Vec<&str>> = HashMap::new();
+    for stanza in states.split("\n\n") {
+        if !stanza.lines().any(|line| line.trim() == "Auto-Installed: 1") {
+            continue;
+        }
+        if let Some(name) = stanza_field(stanza, "Package:") {
+            automatic.entry(name).or_default().push(stanza_field(stanza, "Architecture:").unwrap_or_default());
+        }
+    }
+    automatic
+}
+
+// Value of a "Field: value" line in a dpkg or apt stanza
+fn stanza_field<'a>(stanza: &'a str, field: &str) -> Option<&'a str> {
+    stanza.lines().find_map(|line| line.strip_prefix(field)).map(str::trim)
+}
+
+// One directory per package next to the ALPM_DB_VERSION file
+fn pacman() -> Option<usize> {
+    Some(subdirectories("/var/lib/pacman/local")?.len())
+}
+
+// One "P:<name>" line per package
+fn apk() -> Option<usize> {
+    let installed = sysroot::read_to_string("/lib/apk/db/installed").ok()?;
+    Some(installed.lines().filter(|line| line.starts_with("P:")).count())
+}
+
+/// Explicitly installed packages and orphans (dependencies nothing needs any more,
+/// like `pacman -Qdt`) from the `desc` files of the local database
+pub fn pacman_explicit_and_orphans() -> Option<(usize, usize)> {
+    let descs: Vec<String> = subdirectories("/var/lib/pacman/local")?
+        .iter()
+        .filter_map(|package| sysroot::read_to_string(package.join("desc")).ok())
+        .collect();
+    Some(explicit_and_orphans(&descs))
+}
+
+fn explicit_and_orphans(descs: &[String]) -> (usize, usize) {
+    // "%NAME%\nfirefox\n\n%REASON%\n1\n\n%DEPENDS%\nglibc\ngtk3>=3.24\n..."
+    let packages: Vec<HashMap<String, Vec<String>>> = descs
+        .iter()
+        .map(|desc| {
+            desc.split("\n\n")
+                .filter_map(|section| {
+                    let mut lines = section.trim().lines();
+                    let name = lines.next()?.strip_prefix('%')?.strip_suffix('%')?;
+                    Some((name.to_string(), lines.map(str::to_string).collect()))
+                })
+                .collect()
+        })
+        .collect();
+
+    // "gtk3>=3.24", "python-pip: for installing modules" and "libfoo.so=1-64" all name "gtk3" etc.
+    let bare = |entry: &str| entry.split(['<', '>', '=', ':']).next().unwrap_or(entry).trim().to_string();
+    let field = |package: &HashMap<String, Vec<String>>, name: &str| package.get(name).cloned().unwrap_or_default();
+    let required: HashSet<String> = packages
+        .iter()
+        .flat_map(|package| [field(package, "DEPENDS"), field(package, "OPTDEPENDS")].concat())
+        .map(|dependency| bare(&dependency))
+        .collect();
+
+    // %REASON% 1 is a dependency; explicit installs usually have no %REASON% at all
+    let is_dependency = |package: &HashMap<String, Vec<String>>| field(package, "REASON").first().is_some_and(|reason| reason == "1");
+    let explicit = packages.iter().filter(|package| !is_dependency(package)).count();
+    let orphans = packages
+        .iter()
+        .filter(|package| is_dependency(package))
+        .filter(|package| {
+            let names = [field(package, "NAME"), field(package, "PROVIDES")].concat();
+            !names.iter().any(|name| required.contains(&bare(name)))
+        })
+        .count();
+    (explicit, orphans)
+}
+
+/// Packages not found in any sync repository, i.e. from the AUR or built locally.
+/// The sync databases are compressed archives, so pacman is asked.
+pub fn pacman_foreign() -> Option<usize> {
+    Some(commands::run("pacman", &["-Qqm"], &[])?.lines().count())
+}
+
+// /var/db/pkg/<category>/<package>-<version>
+fn portage() -> Option<usize> {
+    let categories = subdirectories("/var/db/pkg")?;
+    Some(categories.iter().filter_map(subdirectories).map(|packages| packages.len()).sum())
+}
+
+fn rpm() -> Option<usize> {
+    // rpm itself exists on some non-RPM distributions; without a database it has nothing to count
+    if !sysroot::exists("/var/lib/rpm") && !sysroot::exists("/usr/lib/sysimage/rpm") {
+        return None;
+    }
+    Some(commands::run("rpm", &["-qa"], &[])?.lines().count())
+}
+
+fn xbps() -> Option<usize> {
+    if !sysroot::exists("/var/db/xbps") {
+        return None;
+    }
+    Some(commands::run("xbps-query", &["-l"], &[])?.lines().count())
+}
+
+// Store paths a profile depends on, as `nix-store --query --requisites` reports them
+fn nix_profile(profile: &Path) -> Option<usize> {
+    if !sysroot::exists(profile) {
+        return None;
+    }
+    let output = commands::run("nix-store", &["--query", "--requisites", profile.to_str()?], &[])?;
+    Some(output.lines().count())
+}
+
+fn nix_system() -> Option<usize> {
+    // NixOS, or nix installed system-wide on another distribution
+    nix_profile(Path::new("/run/current-system")).or_else(|| nix_profile(Path::new("/nix/var/nix/profiles/default")))
+}
+
+fn nix_user() -> Option<usize> {
+    // Newer installs keep the profile under XDG state
+    let home = dirs::home_dir()?;
+    [home.join(".local/state/nix/profile"), home.join(".nix-profile")]
+        .iter()
+        .find_map(|profile| nix_profile(profile))
+}
+
+// /snap/<name>/<revision>; /snap/bin holds the command wrappers
+fn snap() -> Option<usize> {
+    let snaps = subdirectories("/snap")?;
+    Some(snaps.iter().filter(|snap| !snap.ends_with("bin")).count())
+}
+
+/// Where Homebrew on Linux may be installed, in the order they're looked at
+pub fn brew_prefixes() -> Vec<PathBuf> {
+    let mut prefixes = vec![PathBuf::from("/home/linuxbrew/.linuxbrew")];
+    prefixes.extend(dirs::home_dir().map(|home| home.join(".linuxbrew")));
+    prefixes.extend(std::env::var_os("HOMEBREW_PREFIX").map(PathBuf::from));
+    prefixes
+}
+
+// Formulae in Cellar and casks in Caskroom of the first Homebrew prefix found
+fn brew() -> Option<usize> {
+    brew_prefixes().iter().find_map(|prefix| {
+        let formulae = subdirectories(prefix.join("Cellar"))?;
+        let casks = subdirectories(prefix.join("Caskroom")).unwrap_or_default();
+        Some(formulae.len() + casks.len())
+    })
+}
+
+fn subdirectories(path: impl AsRef<Path>) -> Option<Vec<PathBuf>> {
+    let entries = sysroot::read_dir(path).ok()?;
+    Some(entries.into_iter().filter(|entry| sysroot::resolve(entry).is_dir()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_installed_dpkg_packages() {
+        let status = "\
+Package: a
+Status: install ok installed
+Architecture: amd64
+
+Package: libc6
+Status: install ok installed
+Architecture: amd64
+Multi-Arch: same
+
+Package: libc6
+Status: install ok installed
+Architecture: i386
+Multi-Arch: same
+
+Package: c
+Status: deinstall ok config-files
+Architecture: amd64
+";
+        let installed = parse_dpkg_status(status);
+        // Both architectures of libc6 count
+        assert_eq!(installed.len(), 3);
+        assert!(installed.contains(&("libc6".to_string(), "i386".to_string())));
+        assert!(!installed.iter().any(|(name, _)| name == "c"));
+    }
+
+    #[test]
+    fn reads_auto_installed_marks() {
+        let states = "\
+Package: libc6
+Architecture: i386
+Auto-Installed: 1
+
+Package: d
+Architecture: amd64
+Auto-Installed: 0
+
+Package: old
+Auto-Installed: 1
+";
+        let automatic = parse_auto_installed(states);
+        assert_eq!(automatic.len(), 2);
+        assert_eq!(automatic["libc6"], ["i386"]);
+        assert_eq!(automatic["old"], [""]);
+    }
+
+    #[test]
+    fn matches_auto_installed_marks_by_architecture() {
+        let installed: HashSet<(String, String)> = [("libc6", "amd64"), ("libc6", "i386"), ("tzdata", "all"), ("old", "amd64")]
+            .map(|(name, architecture)| (name.to_string(), architecture.to_string()))
+            .into();
+        let states = "\
+Package: libc6
+Architecture: i386
+Auto-Installed: 1
+
+Package: tzdata
+Architecture: amd64
+Auto-Installed: 1
+
+Package: old
+Auto-Installed: 1
+";
+        // libc6:amd64 was installed on purpose
+        assert_eq!(count_automatic(&installed, states), 3);
+    }
+
+    #[test]
+    fn finds_explicit_packages_and_orphans() {
+        let descs = [
+            "%NAME%\nfirefox\n\n%DEPENDS%\ngtk3>=3.24\nlibfoo.so=1-64\n\n%OPTDEPENDS%\npython-pip: for installing modules\n",
+            // Required by firefox
+            "%NAME%\ngtk3\n\n%REASON%\n1\n",
+            // Required through what it provides
+            "%NAME%\nfoo\n\n%REASON%\n1\n\n%PROVIDES%\nlibfoo.so=1-64\n",
+            // Optional dependency
+            "%NAME%\npython-pip\n\n%REASON%\n1\n",
+            // Nothing needs it any more
+            "%NAME%\nold-lib\n\n%REASON%\n1\n",
+            // %REASON% 0 is an explicit install
+            "%NAME%\nvim\n\n%REASON%\n0\n",
+        ]
+        .map(str::to_string);
+        assert_eq!(explicit_and_orphans(&descs), (2, 1));
+    }
+}
@@ -0,0 +1,159 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use crate::sysroot;
+
+/// What an ancestor of termgreet is, as far as the greeting cares
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Shell,
+    Multiplexer,
+    Terminal,
+}
+
+/// One process in the chain from termgreet up to init
+#[derive(Debug, Clone)]
+pub struct Process {
+    pub pid: u32,
+    pub ppid: u32,
+    /// Kernel command name, truncated to 15 characters
+    pub comm: String,
+    pub exe: Option<PathBuf>,
+    pub cmdline: Vec<String>,
+}
+
+impl Process {
+    fn read(pid: &str) -> Option<Self> {
+        let dir = Path::new("/proc").join(pid);
+        let stat = sysroot::read_to_string(dir.join("stat")).ok()?;
+
+        // "1234 (comm) S 1200 ..."; comm may itself contain spaces and parentheses
+        let open = stat.find('(')?;
+        let close = stat.rfind(')')?;
+        let pid = stat[..open].trim().parse().ok()?;
+        let ppid = stat[close + 1..].split_whitespace().nth(1)?.parse().ok()?;
+        let comm = sysroot::read_to_string(dir.join("comm"))
+            .map(|comm| comm.trim_end().to_string())
+            .unwrap_or_else(|_| stat[open + 1..close].to_string());
+
+        // exe is unreadable for other users' processes; cmdline is usually still there
+        let exe = sysroot::resolve(dir.join("exe")).read_link().ok();
+        let cmdline = sysroot::read_to_string(dir.join("cmdline"))
+            .map(|cmdline| cmdline.split('\0').filter(|arg| !arg.is_empty()).map(str::to_string).collect())
+            .unwrap_or_default();
+
+        Some(Self { pid, ppid, comm, exe, cmdline })
+    }
+
+    // Candidate program names: the executable, argv[0] and comm. They differ for
+    // interpreted programs (terminator runs as python3) and truncated comms.
+    fn names(&self) -> Vec<String> {
+        let file_name = |path: &str| path.rsplit('/').next().unwrap_or(path).to_string();
+        let exe = self
+            .exe
+            .as_ref()
+            .and_then(|exe| exe.to_str())
+            // Replaced binaries show up as "/usr/bin/foo (deleted)"
+            .map(|exe| file_name(exe.trim_end_matches(" (deleted)")));
+        let argv0 = self.cmdline.first().map(|arg| file_name(arg));
+
+        [exe, argv0, Some(self.comm.clone())]
+            .into_iter()
+            .flatten()
+            // Login shells are started as "-bash"
+            .map(|name| name.trim_start_matches('-').to_string())
+            .filter(|name| !name.is_empty())
+            .collect()
+    }
+
+    /// Program name without the path
+    pub fn name(&self) -> String {
+        self.names().into_iter().next().unwrap_or_default()
+    }
+
+    pub fn role(&self) -> Option<Role> {
+        // The tmux server renames itself to "tmux: server"
+        if self.comm.starts_with("tmux") {
+            return Some(Role::Multiplexer);
+        }
+        self.names().iter().find_map(|name| classify(name))
+    }
+
+    /// Name as shown in the greeting; helper binaries map to the program users know
+    pub fn display_name(&self) -> String {
+        if self.comm.starts_with("tmux") {
+            return "tmux".to_string();
+        }
+        let names = self.names();
+        let Some(name) = names.iter().find(|name| classify(name).is_some()) else {
+            return self.name();
+        };
+        let canonical = match name.as_str() {
+            "wezterm-gui" => "wezterm",
+            "gnome-terminal-server" => "gnome-terminal",
+            "footclient" => "foot",
+            "kgx" => "gnome-console",
+            "warp-terminal" => "warp",
+            "rxvt-unicode" => "urxvt",
+            "SCREEN" => "screen",
+            _ => name,
+        };
+        canonical.to_string()
+    }
+}
+
+fn classify(name: &str) -> Option<Role> {
+    match name {
+        "bash" | "zsh" | "fish" | "sh" | "dash" | "ksh" | "mksh" | "oksh" | "tcsh" | "csh" | "nu" | "elvish"
+        | "xonsh" | "ion" | "pwsh" | "osh" | "ysh" | "yash" | "oil" => Some(Role::Shell),
+        "tmux" | "screen" | "SCREEN" | "zellij" | "byobu" | "abduco" | "dvtm" => Some(Role::Multiplexer),
+        "alacritty" | "kitty" | "ghostty" | "wezterm" | "wezterm-gui" | "gnome-terminal" | "gnome-terminal-server"
+        | "kgx" | "konsole" | "xterm" | "urxvt" | "rxvt" | "rxvt-unicode" | "terminator" | "tilix" | "st" | "foot"
+        | "footclient" | "rio" | "contour" | "xfce4-terminal" | "tabby" | "warp" | "warp-terminal" | "hyper"
+        | "terminology" | "qterminal" | "lxterminal" | "mate-terminal" | "sakura" | "blackbox" | "cool-retro-term"
+        | "deepin-terminal" | "tilda" | "guake" | "yakuake" | "st-256color" | "wayst" | "zutty" | "mlterm" => {
+            Some(Role::Terminal)
+        }
+        _ => None,
+    }
+}
+
+/// Parent, grandparent, ... of termgreet up to (not including) init.
+///
+/// Walked once per run from `/proc/self`, so the shell, multiplexer and
+/// terminal modules all share it.
+pub fn ancestors() -> &'static [Process] {
+    static ANCESTORS: OnceLock<Vec<Process>> = OnceLock::new();
+    ANCESTORS.get_or_init(|| {
+        let mut chain = Vec::new();
+        let Some(current) = Process::read("self") else {
+            return chain;
+        };
+
+        let mut ppid = current.ppid;
+        while ppid > 1 {
+            let Some(parent) = Process::read(&ppid.to_string()) else {
+                break;
+            };
+            // A hand-made sysroot could contain a cycle
+            if parent.pid == current.pid || chain.iter().any(|process: &Process| process.pid == parent.pid) {
+                break;
+            }
+            ppid = parent.ppid;
+            chain.push(parent);
+        }
+        chain
+    })
+}
+
+/// Closest ancestor with the given role. Shells and multiplexers are only
+/// looked for below the terminal; above it they belong to whatever started it.
+pub fn nearest(role: Role) -> Option<&'static Process> {
+    for process in ancestors() {
+        match process.role() {
+            Some(found) if found == role => return Some(process),
+            Some(Role::Terminal) => return None,
+            _ => {}
+        }
+    }
+    None
+}
@@ -0,0 +1,134 @@
+use std::net::IpAddr;
+use std::time::Duration;
+use serde_json::Value as Json;
+use ureq::config::IpFamily;
+use crate::commands;
+use crate::config::PublicIpLookupConfig;
+
+// Used when a lookup runs outside a collector's time budget
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// What an echo service reported about the address it saw
+#[derive(Debug, Clone)]
+pub struct Lookup {
+    pub address: IpAddr,
+    pub isp: Option<String>,
+    /// Autonomous system number, e.g. `AS13335`
+    pub asn: Option<String>,
+    pub country: Option<String>,
+}
+
+/// The public address for one IP family: the answer of the first endpoint that
+/// returns an address of that family within the collector's remaining time
+pub fn lookup(config: &PublicIpLookupConfig, ipv6: bool) -> Option<Lookup> {
+    let proxy = proxy(config);
+    config.endpoints.iter().find_map(|endpoint| {
+        // Each request only gets what earlier endpoints left of the budget
+        let timeout = commands::remaining().unwrap_or(DEFAULT_TIMEOUT);
+        if timeout.is_zero() {
+            return None;
+        }
+        let get = || {
+            let agent: ureq::Agent = ureq::Agent::config_builder()
+                .timeout_global(Some(timeout))
+                .ip_family(if ipv6 { IpFamily::Ipv6Only } else { IpFamily::Ipv4Only })
+                .proxy(proxy.clone())
+                .user_agent("termgreet")
+                .build()
+                .into();
+            agent.get(endpoint).call().ok()?.body_mut().read_to_string().ok()
+        };
+        // Recorded and replayed like external commands
+        let body = commands::http_get(endpoint, ipv6, &get)?;
+        parse(&body).filter(|lookup| lookup.address.is_ipv6() == ipv6)
+    })
+}
+
+fn proxy(config: &PublicIpLookupConfig) -> Option<ureq::Proxy> {
+    match config.proxy.as_deref() {
+        None => ureq::Proxy::try_from_env(),
+        // An empty proxy bypasses the one from the environment
+        Some("") => None,
+        Some(proxy) => match ureq::Proxy::new(proxy) {
+            Ok(proxy) => Some(proxy),
+            Err(error) => {
+                eprintln!("Warning: Ignoring invalid proxy {}: {}", proxy, error);
+                None
+            }
+        },
+    }
+}
+
+// Either a bare address ("203.0.113.7\n") or a JSON object in one of the common
+// shapes: ipinfo.io ({"ip", "org": "AS13335 Cloudflare, Inc.", "country"}),
+// ip-api.com ({"query", "isp", "as", "countryCode"}) or ipapi.co ({"ip", "org", "asn", "country_code"})
+fn parse(body: &str) -> Option<Lookup> {
+    let body = body.trim();
+    if let Ok(address) = body.parse() {
+        return Some(Lookup { address, isp: None, asn: None, country: None });
+    }
+
+    let json: Json = serde_json::from_str(body).ok()?;
+    let text = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|key| json.get(*key)?.as_str())
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .map(str::to_string)
+    };
+    let address = text(&["ip", "query", "address"])?.parse().ok()?;
+
+    // "AS13335 Cloudflare, Inc." carries both the number and the name
+    let (org_asn, org_name) = match text(&["org", "as"]) {
+        Some(org) if org.starts_with("AS") => match org.split_once(' ') {
+            Some((asn, name)) => (Some(asn.to_string()), Some(name.trim().to_string())),
+            None => (Some(org), None),
+        },
+        org => (None, org),
+    };
+    let asn = text(&["asn"])
+        .or_else(|| json.get("asn")?.as_u64().map(|number| number.to_string()))
+        .map(|asn| if asn.starts_with("AS") { asn } else { format!("AS{}", asn) })
+        .or(org_asn);
+
+    Some(Lookup {
+        address,
+        isp: text(&["isp"]).or(org_name),
+        asn,
+        country: text(&["country", "country_code", "countryCode"]),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_bare_addresses() {
+        let lookup = parse("203.0.113.7\n").unwrap();
+        assert_eq!(lookup.address.to_string(), "203.0.113.7");
+        assert!(lookup.isp.is_none());
+        assert!(parse("2001:db8::1").unwrap().address.is_ipv6());
+        assert!(parse("<html>rate limited</html>").is_none());
+    }
+
+    #[test]
+    fn parses_ipinfo() {
+        let lookup = parse(r#"{"ip": "203.0.113.7", "org": "AS13335 Cloudflare, Inc.", "country": "DE"}"#).unwrap();
+        assert_eq!(lookup.asn.as_deref(), Some("AS13335"));
+        assert_eq!(lookup.isp.as_deref(), Some("Cloudflare, Inc."));
+        assert_eq!(lookup.country.as_deref(), Some("DE"));
+    }
+
+    #[test]
+    fn parses_ip_api_and_ipapi() {
+        let lookup = parse(r#"{"query": "203.0.113.7", "isp": "Example ISP", "as": "AS64500 Example", "countryCode": "NL"}"#).unwrap();
+        assert_eq!(lookup.isp.as_deref(), Some("Example ISP"));
+        assert_eq!(lookup.asn.as_deref(), Some("AS64500"));
+        assert_eq!(lookup.country.as_deref(), Some("NL"));
+
+        let lookup = parse(r#"{"ip": "203.0.113.7", "org": "Example ISP", "asn": 64500, "country_code": "FR"}"#).unwrap();
+        assert_eq!(lookup.isp.as_deref(), Some("Example ISP"));
+        assert_eq!(lookup.asn.as_deref(), Some("AS64500"));
+    }
+}
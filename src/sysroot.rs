@@ -0,0 +1,55 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+// Directory that stands in for `/`; unset means the live system
+static ROOT: OnceLock<PathBuf> = OnceLock::new();
+
+/// Read system files from `root` instead of `/` for the rest of the run.
+/// Only the first call has an effect.
+pub fn set(root: PathBuf) {
+    let _ = ROOT.set(root);
+}
+
+/// True when reading a snapshot of another machine
+pub fn is_set() -> bool {
+    ROOT.get().is_some()
+}
+
+/// Where an absolute system path like `/etc/os-release` actually lives.
+/// Relative paths and paths in the user's home directory are left alone.
+pub fn resolve(path: impl AsRef<Path>) -> PathBuf {
+    let path = path.as_ref();
+    match (ROOT.get(), path.strip_prefix("/")) {
+        (Some(root), Ok(relative)) if !in_home(path) => root.join(relative),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn in_home(path: &Path) -> bool {
+    dirs::home_dir().is_some_and(|home| path.starts_with(home))
+}
+
+pub fn read_to_string(path: impl AsRef<Path>) -> io::Result<String> {
+    fs::read_to_string(resolve(path))
+}
+
+pub fn exists(path: impl AsRef<Path>) -> bool {
+    resolve(path).exists()
+}
+
+pub fn metadata(path: impl AsRef<Path>) -> io::Result<fs::Metadata> {
+    fs::metadata(resolve(path))
+}
+
+/// Entries of a directory as paths on the target system (`/sys/class/drm/card0`),
+/// so they can be passed back into the functions of this module
+pub fn read_dir(path: impl AsRef<Path>) -> io::Result<Vec<PathBuf>> {
+    let path = path.as_ref();
+    let entries = fs::read_dir(resolve(path))?
+        .flatten()
+        .map(|entry| path.join(entry.file_name()))
+        .collect();
+    Ok(entries)
+}
@@ -1,220 +1,238 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs;
-use std::process::Command;
-use std::sync::OnceLock;
+use std::sync::{mpsc, Arc, OnceLock};
+use std::thread::{self, ThreadId};
+use std::time::Instant;
 use sysinfo::System;
-use rayon::prelude::*;
+use crate::cache;
+use crate::commands;
+use crate::gpu;
+use crate::hwmon::{self, Role as HwmonRole};
+use crate::kmod::KernelModule;
+use crate::mounts::{self, Mount, Unmeasured, Usage};
+use crate::net;
+use crate::packages;
+use crate::process::{self, Role};
+use crate::public_ip;
+use crate::sysroot;
+use crate::template;
+use crate::updates;
+use crate::config::{CacheConfig, ModulesConfig, PackageBreakdown, PackageCountsConfig, PublicIpLookupConfig, TemperatureConfig, UpdateCheckConfig};
+use crate::modules::{enabled_modules, CollectContext, Cost, Dependency, Module};
+use crate::value::{Fields, Unit, Value};
 
 #[derive(Debug, Clone)]
 pub struct SystemInfo {
-    pub data: HashMap<String, String>,
+    /// Collected entries per module name, before formatting
+    pub data: HashMap<String, Vec<Fields>>,
+    /// Modules that ran out of time before producing anything
+    pub timed_out: HashSet<String>,
+}
+
+/// Module collection in progress; results arrive as collectors finish
+pub struct Collection {
+    info: SystemInfo,
+    receiver: mpsc::Receiver<(&'static str, Vec<Fields>, bool)>,
+    // Collectors still running, by the thread they run on
+    pending: HashMap<&'static str, ThreadId>,
+    deadline: Instant,
+}
+
+impl Collection {
+    /// Wait for the next module to finish and return its name. Returns `None` once
+    /// every module is done or the global deadline passed; stragglers are then
+    /// marked as timed out and abandoned.
+    pub fn next(&mut self) -> Option<&'static str> {
+        if !self.pending.is_empty() {
+            let remaining = self.deadline.saturating_duration_since(Instant::now());
+            if let Ok((name, entries, timed_out)) = self.receiver.recv_timeout(remaining) {
+                self.pending.remove(name);
+                // Modules that don't apply return no entries
+                if timed_out {
+                    self.info.timed_out.insert(name.to_string());
+                } else if !entries.is_empty() {
+                    self.info.data.insert(name.to_string(), entries);
+                }
+                return Some(name);
+            }
+        }
+        
+        // Nothing waits for the stragglers anymore, so neither should their commands
+        for (name, thread) in self.pending.drain() {
+            commands::kill_started_by(thread);
+            self.info.timed_out.insert(name.to_string());
+        }
+        None
+    }
+    
+    pub fn is_pending(&self, name: &str) -> bool {
+        self.pending.contains_key(name)
+    }
+    
+    /// Everything collected so far
+    pub fn info(&self) -> &SystemInfo {
+        &self.info
+    }
+    
+    pub fn into_info(self) -> SystemInfo {
+        self.info
+    }
 }
 
 impl SystemInfo {
     pub fn gather_with_config(config: &crate::config::Config) -> Self {
+        let mut collection = Self::start_collection(config);
+        while collection.next().is_some() {}
+        collection.into_info()
+    }
+    
+    /// Start all enabled collectors in the background; cached modules are available right away
+    pub fn start_collection(config: &crate::config::Config) -> Collection {
+        let enabled = enabled_modules(&config.modules);
+        
+        let timeouts = &config.modules.timeouts;
+        let start = Instant::now();
+        let global_deadline = start + timeouts.global_budget();
+        
         // Initialize optimized sysinfo - only refresh what we need
-        let sys = Self::create_optimized_system(config);
+        let sys = Arc::new(Self::create_optimized_system(&enabled));
+        let modules_config = Arc::new(config.modules.clone());
         
         // Pre-cache commonly used files
         Self::cache_system_files();
         
-        // Define all possible module collectors with their conditions
-        let module_collectors: Vec<(&str, Box<dyn Fn() -> String + Send + Sync>)> = vec![
-            // OS Information - Fast, no external commands
-            ("OS", Box::new(|| Self::get_os_info())),
-            ("KERNEL", Box::new(|| Self::get_kernel_version())),
-            ("LINUX", Box::new(|| Self::get_linux_info())),
-            ("UPTIME", Box::new(|| Self::format_uptime(System::uptime()))),
-            ("OS_AGE", Box::new(|| Self::get_os_age())),
-            
-            // Environment - Medium speed
-            ("DE", Box::new(|| Self::get_desktop_environment())),
-            ("WM", Box::new(|| Self::get_window_manager())),
-            ("USER", Box::new(|| Self::get_user_info())),
-            ("HOSTNAME", Box::new(|| Self::get_hostname_info())),
-            ("USER_AT_HOST", Box::new(|| Self::get_user_at_host_info())),
-            ("LOCALE", Box::new(|| Self::get_locale())),
-            ("THEME", Box::new(|| Self::get_theme())),
-            ("ICONS", Box::new(|| Self::get_icons())),
-        ];
-        
-        // System-dependent collectors (need sys reference)
-        let sys_collectors: Vec<(&str, Box<dyn Fn(&System) -> String + Send + Sync>)> = vec![
-            ("CPU", Box::new(|sys| Self::get_cpu_info(sys))),
-            ("MEMORY", Box::new(|sys| Self::get_memory_info(sys))),
-            ("DISK", Box::new(|sys| Self::get_disk_info(sys))),
-        ];
-        
-        // Slow collectors (external commands) - these benefit most from parallelization
-        let slow_collectors: Vec<(&str, Box<dyn Fn() -> String + Send + Sync>)> = vec![
-            ("GPU", Box::new(|| Self::get_gpu_info())),
-            ("GPU_DRIVER", Box::new(|| Self::get_gpu_driver_info())),
-            ("RESOLUTION", Box::new(|| Self::get_resolution())),
-            ("NETWORK", Box::new(|| Self::get_network_info())),
-            ("PUBLIC_IP", Box::new(|| Self::get_public_ip_info())),
-            ("DYSK", Box::new(|| Self::get_dysk_info())),
-            ("CPU_TEMP", Box::new(|| Self::get_cpu_temperature())),
-            ("GPU_TEMP", Box::new(|| Self::get_gpu_temperature())),
-            ("TEMP_COMBINED", Box::new(|| Self::get_temp_combined())),
-            ("FONT", Box::new(|| Self::get_font_info())),
-        ];
-        
-        // Version-dependent collectors
-        let version_collectors: Vec<(&str, Box<dyn Fn(bool) -> String + Send + Sync>)> = vec![
-            ("SHELL", Box::new(|show_versions| {
-                if show_versions { Self::get_shell_with_version() } else { Self::get_shell() }
-            })),
-            ("TERMINAL", Box::new(|show_versions| {
-                if show_versions { Self::get_terminal_with_version() } else { Self::get_terminal() }
-            })),
-            ("TERMINAL_SHELL_COMBINED", Box::new(|show_versions| {
-                Self::get_terminal_shell_combined(show_versions)
-            })),
-        ];
-        
-        // Parallel collection of fast modules (only enabled ones)
-        let fast_results: Vec<(String, String)> = module_collectors
-            .into_par_iter()
-            .filter_map(|(key, collector)| {
-                let should_collect = match key {
-                    "OS" => config.modules.os,
-                    "KERNEL" => config.modules.kernel,
-                    "LINUX" => config.modules.linux,
-                    "UPTIME" => config.modules.uptime,
-                    "OS_AGE" => config.modules.os_age,
-                    "DE" => config.modules.de,
-                    "WM" => config.modules.wm,
-                    "USER" => config.modules.user,
-                    "HOSTNAME" => config.modules.hostname,
-                    "USER_AT_HOST" => config.modules.user_at_host,
-                    "LOCALE" => config.modules.locale,
-                    "THEME" => config.modules.theme,
-                    "ICONS" => config.modules.icons,
-                    _ => false,
-                };
-                
-                if should_collect {
-                    Some((key.to_string(), collector()))
-                } else {
-                    None
-                }
-            })
-            .collect();
+        // Start slow collectors (external commands) first so they overlap with the fast ones
+        let mut scheduled = enabled;
+        scheduled.sort_by_key(|module| module.cost() != Cost::Slow);
         
-        // Parallel collection of slow modules (only enabled ones)
-        let slow_results: Vec<(String, String)> = slow_collectors
-            .into_par_iter()
-            .filter_map(|(key, collector)| {
-                let should_collect = match key {
-                    "GPU" => config.modules.gpu,
-                    "GPU_DRIVER" => config.modules.gpu_driver,
-                    "RESOLUTION" => config.modules.resolution,
-                    "NETWORK" => config.modules.network,
-                    "PUBLIC_IP" => config.modules.public_ip,
-                    "DYSK" => config.modules.dysk,
-                    "CPU_TEMP" => config.modules.cpu_temp,
-                    "GPU_TEMP" => config.modules.gpu_temp,
-                    "TEMP_COMBINED" => config.modules.temp_combined,
-                    "FONT" => config.modules.font,
-                    _ => false,
-                };
-                
-                if should_collect {
-                    Some((key.to_string(), collector()))
-                } else {
-                    None
+        // One thread per module: most of them wait on external commands, and a
+        // collector that hangs must not hold up the others or the prompt
+        let (sender, receiver) = mpsc::channel();
+        let mut pending = HashMap::new();
+        let mut data = HashMap::new();
+        for module in scheduled {
+            let cache_ttl = config.modules.cache.ttl(module);
+            if let Some(entries) = cache_ttl.and_then(|ttl| cache::load(module, ttl, &config.modules)) {
+                if !entries.is_empty() {
+                    data.insert(module.name().to_string(), entries);
                 }
-            })
-            .collect();
-        
-        // Sequential collection of system-dependent modules
-        let sys_results: Vec<(String, String)> = sys_collectors
-            .into_iter()
-            .filter_map(|(key, collector)| {
-                let should_collect = match key {
-                    "CPU" => config.modules.cpu,
-                    "MEMORY" => config.modules.memory,
-                    "DISK" => config.modules.disk,
-                    _ => false,
+                continue;
+            }
+            
+            let sender = sender.clone();
+            let sys = Arc::clone(&sys);
+            let modules_config = Arc::clone(&modules_config);
+            let show_versions = config.modules.show_versions;
+            let deadline = (start + timeouts.module_budget(module.name())).min(global_deadline);
+            
+            let collector = thread::spawn(move || {
+                let ctx = CollectContext {
+                    sys: &sys,
+                    show_versions,
+                    config: &modules_config,
                 };
-                
-                if should_collect {
-                    Some((key.to_string(), collector(&sys)))
-                } else {
-                    None
-                }
-            })
-            .collect();
+                let (entries, deadline_hit) = commands::with_deadline(deadline, || module.collect(&ctx));
+                // Partial results (e.g. GPU name without VRAM) are still worth showing
+                let timed_out = deadline_hit && !entries.iter().any(Fields::is_available);
+                // Only complete results are cached, so failed lookups are retried next time
+                if cache_ttl.is_some() && !deadline_hit && entries.iter().all(Fields::is_available) {
+                    cache::store(module, &entries, &modules_config);
+                }
+                let _ = sender.send((module.name(), entries, timed_out));
+            });
+            pending.insert(module.name(), collector.thread().id());
+        }
+        drop(sender);
+        
+        Collection {
+            info: Self {
+                data,
+                timed_out: HashSet::new(),
+            },
+            receiver,
+            pending,
+            deadline: global_deadline,
+        }
+    }
+    
+    /// Render a module's entries with its configured (or default) format string.
+    ///
+    /// Entries with nothing available are skipped; returns `None` if nothing is left to show.
+    /// Modules that ran out of time render as "timed out".
+    pub fn render(&self, module: &dyn Module, config: &ModulesConfig) -> Option<String> {
+        if self.timed_out.contains(module.name()) {
+            return Some("timed out".to_string());
+        }
         
-        // Version-dependent modules
-        let version_results: Vec<(String, String)> = version_collectors
+        let rendered: Vec<String> = self
+            .rendered_entries(module, config)?
             .into_iter()
-            .filter_map(|(key, collector)| {
-                let should_collect = match key {
-                    "SHELL" => config.modules.shell,
-                    "TERMINAL" => config.modules.terminal,
-                    "TERMINAL_SHELL_COMBINED" => config.modules.terminal_shell_combined,
-                    _ => false,
-                };
-                
-                if should_collect {
-                    Some((key.to_string(), collector(config.modules.show_versions)))
-                } else {
-                    None
-                }
-            })
+            .map(|(text, _)| text)
             .collect();
         
-        // Optional modules (only if available and enabled)
-        let mut optional_results = Vec::new();
-        
-        if config.modules.battery {
-            if let Some(battery) = Self::get_battery_info() {
-                optional_results.push(("BATTERY".to_string(), battery));
-            }
-        }
-        
-        if config.modules.packages {
-            if let Some(packages) = Self::get_package_count() {
-                optional_results.push(("PACKAGES".to_string(), packages));
-            }
+        if rendered.is_empty() {
+            None
+        } else {
+            Some(rendered.join(module.entry_separator()))
         }
-        
-        if config.modules.flatpak_packages {
-            if let Some(flatpak_packages) = Self::get_flatpak_packages() {
-                optional_results.push(("FLATPAK_PACKAGES".to_string(), flatpak_packages));
-            }
+    }
+    
+    /// Like `render`, split into output lines, each with the color its entry asks for
+    /// (`None` for the configured info color)
+    pub fn render_lines(&self, module: &dyn Module, config: &ModulesConfig) -> Option<Vec<(String, Option<&'static str>)>> {
+        if self.timed_out.contains(module.name()) {
+            return Some(vec![("timed out".to_string(), None)]);
         }
         
-        if config.modules.packages_combined {
-            if let Some(combined_packages) = Self::get_combined_packages() {
-                optional_results.push(("PACKAGES_COMBINED".to_string(), combined_packages));
-            }
+        // Entries that share a line can't have colors of their own
+        if !module.entry_separator().contains('\n') {
+            return self.render(module, config).map(|text| text.lines().map(|line| (line.to_string(), None)).collect());
         }
         
-        // Combine all results
-        let mut data = HashMap::new();
+        let lines: Vec<(String, Option<&'static str>)> = self
+            .rendered_entries(module, config)?
+            .into_iter()
+            .flat_map(|(text, color)| text.lines().map(|line| (line.to_string(), color)).collect::<Vec<_>>())
+            .collect();
         
-        for (key, value) in fast_results.into_iter()
-            .chain(slow_results.into_iter())
-            .chain(sys_results.into_iter())
-            .chain(version_results.into_iter())
-            .chain(optional_results.into_iter()) {
-            data.insert(key, value);
+        if lines.is_empty() {
+            None
+        } else {
+            Some(lines)
         }
-        
-        Self { data }
     }
     
-    fn create_optimized_system(config: &crate::config::Config) -> System {
+    // Available entries rendered with the module's format, with their color override
+    fn rendered_entries(&self, module: &dyn Module, config: &ModulesConfig) -> Option<Vec<(String, Option<&'static str>)>> {
+        let format = config.format(module);
+        // Lists are joined like the entries, but stay on their entry's line
+        let list_separator = match module.entry_separator() {
+            separator if separator.contains('\n') => ", ",
+            separator => separator,
+        };
+        let rendered = self
+            .data
+            .get(module.name())?
+            .iter()
+            .filter(|fields| fields.is_available())
+            .map(|fields| (template::render(format, fields, list_separator), module.entry_color(fields)))
+            .filter(|(text, _)| !text.trim().is_empty())
+            .collect();
+        Some(rendered)
+    }
+    
+    fn create_optimized_system(modules: &[&'static dyn Module]) -> System {
         let mut sys = System::new();
         
         // Only refresh components we actually need
-        if config.modules.cpu {
+        let needs = |dependency: Dependency| {
+            modules.iter().any(|module| module.dependencies().contains(&dependency))
+        };
+        if needs(Dependency::Cpu) {
             sys.refresh_cpu();
         }
-        if config.modules.memory {
+        if needs(Dependency::Memory) {
             sys.refresh_memory();
         }
         // Note: disk info is gathered via external commands, not sysinfo
@@ -229,9 +247,9 @@ impl SystemInfo {
         static PROC_UPTIME: OnceLock<Option<String>> = OnceLock::new();
         
         // Pre-read common files
-        OS_RELEASE.get_or_init(|| fs::read_to_string("/etc/os-release").ok());
-        PROC_VERSION.get_or_init(|| fs::read_to_string("/proc/version").ok());
-        PROC_UPTIME.get_or_init(|| fs::read_to_string("/proc/uptime").ok());
+        OS_RELEASE.get_or_init(|| sysroot::read_to_string("/etc/os-release").ok());
+        PROC_VERSION.get_or_init(|| sysroot::read_to_string("/proc/version").ok());
+        PROC_UPTIME.get_or_init(|| sysroot::read_to_string("/proc/uptime").ok());
     }
     
     // Helper to get cached file content
@@ -240,53 +258,34 @@ impl SystemInfo {
         static PROC_VERSION: OnceLock<Option<String>> = OnceLock::new();
         
         match path {
-            "/etc/os-release" => OS_RELEASE.get_or_init(|| fs::read_to_string(path).ok()).clone(),
-            "/proc/version" => PROC_VERSION.get_or_init(|| fs::read_to_string(path).ok()).clone(),
-            _ => fs::read_to_string(path).ok(),
-        }
-    }
-
-    fn get_os_info() -> String {
-        if let Some(content) = Self::get_cached_file("/etc/os-release") {
-            for line in content.lines() {
-                if line.starts_with("PRETTY_NAME=") {
-                    return line.split('=')
-                        .nth(1)
-                        .unwrap_or("Unknown")
-                        .trim_matches('"')
-                        .to_string();
-                }
-            }
+            "/etc/os-release" => OS_RELEASE.get_or_init(|| sysroot::read_to_string(path).ok()).clone(),
+            "/proc/version" => PROC_VERSION.get_or_init(|| sysroot::read_to_string(path).ok()).clone(),
+            _ => sysroot::read_to_string(path).ok(),
         }
-        "Unknown".to_string()
     }
 
-    fn get_kernel_version() -> String {
-        Self::run_command("uname", &["-r"])
-            .unwrap_or_else(|| "Unknown".to_string())
+    pub(crate) fn get_os_info() -> Option<String> {
+        let content = Self::get_cached_file("/etc/os-release")?;
+        content
+            .lines()
+            .find_map(|line| line.strip_prefix("PRETTY_NAME="))
+            .map(|name| name.trim_matches('"').to_string())
     }
 
-    fn get_linux_info() -> String {
-        let os = Self::get_os_info();
-        let kernel = Self::get_kernel_version();
-        format!("{} - {}", os, kernel)
+    pub(crate) fn get_kernel_version() -> Option<String> {
+        sysroot::read_to_string("/proc/sys/kernel/osrelease")
+            .ok()
+            .or_else(|| Self::run_command("uname", &["-r"]))
+            .map(|version| version.trim().to_string())
     }
 
-    fn format_uptime(uptime_seconds: u64) -> String {
-        let days = uptime_seconds / 86400;
-        let hours = (uptime_seconds % 86400) / 3600;
-        let minutes = (uptime_seconds % 3600) / 60;
-
-        if days > 0 {
-            format!("{} days, {} hours, {} mins", days, hours, minutes)
-        } else if hours > 0 {
-            format!("{} hours, {} mins", hours, minutes)
-        } else {
-            format!("{} mins", minutes)
-        }
+    pub(crate) fn get_linux_info() -> Fields {
+        Fields::new()
+            .with("os", Value::text_or(Self::get_os_info(), "no PRETTY_NAME in os-release"))
+            .with("kernel", Value::text_or(Self::get_kernel_version(), "uname failed"))
     }
 
-    fn get_desktop_environment() -> String {
+    pub(crate) fn get_desktop_environment() -> Option<String> {
         // Check common DE environment variables
         let de_vars = [
             "XDG_CURRENT_DESKTOP",
@@ -298,15 +297,15 @@ impl SystemInfo {
         for var in &de_vars {
             if let Ok(value) = env::var(var) {
                 if !value.is_empty() {
-                    return value;
+                    return Some(value);
                 }
             }
         }
 
-        "Unknown".to_string()
+        None
     }
 
-    fn get_window_manager() -> String {
+    pub(crate) fn get_window_manager() -> Option<String> {
         // Check XDG_SESSION_TYPE first
         let session_type = env::var("XDG_SESSION_TYPE").unwrap_or_default();
         
@@ -325,14 +324,14 @@ impl SystemInfo {
             
             for (process, name) in &wayland_wms {
                 if Self::run_command("pgrep", &["-x", process]).is_some() {
-                    return name.to_string();
+                    return Some(name.to_string());
                 }
             }
             
             // Check WAYLAND_DISPLAY for compositor info
             if let Ok(display) = env::var("WAYLAND_DISPLAY") {
                 if !display.is_empty() {
-                    return "Wayland Compositor".to_string();
+                    return Some("Wayland Compositor".to_string());
                 }
             }
         }
@@ -344,7 +343,7 @@ impl SystemInfo {
                 if let Some(wm_name) = output.split('=').nth(1) {
                     let wm_name = wm_name.trim().trim_matches('"').trim();
                     if !wm_name.is_empty() && wm_name != "(null)" {
-                        return wm_name.to_string();
+                        return Some(wm_name.to_string());
                     }
                 }
             }
@@ -370,7 +369,7 @@ impl SystemInfo {
             
             for (process, name) in &x11_wms {
                 if Self::run_command("pgrep", &["-x", process]).is_some() {
-                    return name.to_string();
+                    return Some(name.to_string());
                 }
             }
         }
@@ -378,43 +377,39 @@ impl SystemInfo {
         // Check environment variables as fallback
         if let Ok(wm) = env::var("WINDOW_MANAGER") {
             if !wm.is_empty() {
-                return wm;
+                return Some(wm);
             }
         }
         
         // Check desktop session
         if let Ok(session) = env::var("DESKTOP_SESSION") {
             match session.to_lowercase().as_str() {
-                "plasma" | "plasmawayland" | "plasmax11" => return "KWin".to_string(),
-                "gnome" | "gnome-wayland" | "gnome-xorg" => return "GNOME Shell".to_string(),
-                "xfce" => return "Xfwm4".to_string(),
-                "lxde" => return "Openbox".to_string(),
-                "i3" => return "i3".to_string(),
+                "plasma" | "plasmawayland" | "plasmax11" => return Some("KWin".to_string()),
+                "gnome" | "gnome-wayland" | "gnome-xorg" => return Some("GNOME Shell".to_string()),
+                "xfce" => return Some("Xfwm4".to_string()),
+                "lxde" => return Some("Openbox".to_string()),
+                "i3" => return Some("i3".to_string()),
                 _ => {}
             }
         }
         
-        "Unknown".to_string()
+        None
     }
 
-    fn get_shell() -> String {
+    pub(crate) fn get_shell() -> Option<String> {
+        // The shell termgreet runs in, which may differ from the login shell in $SHELL
+        if let Some(shell) = process::nearest(Role::Shell) {
+            return Some(shell.display_name());
+        }
+        
         env::var("SHELL")
-            .map(|shell| {
-                shell.split('/').last().unwrap_or("Unknown").to_string()
-            })
-            .unwrap_or_else(|_| "Unknown".to_string())
+            .ok()
+            .and_then(|shell| shell.rsplit('/').next().map(str::to_string))
+            .filter(|shell| !shell.is_empty())
     }
     
-    fn get_shell_with_version() -> String {
-        let shell_name = env::var("SHELL")
-            .map(|shell| {
-                shell.split('/').last().unwrap_or("Unknown").to_string()
-            })
-            .unwrap_or_else(|_| "Unknown".to_string());
-        
-        if shell_name == "Unknown" {
-            return shell_name;
-        }
+    pub(crate) fn get_shell_with_version() -> Option<String> {
+        let shell_name = Self::get_shell()?;
         
         // Try to get version for common shells
         match shell_name.as_str() {
@@ -426,10 +421,10 @@ impl SystemInfo {
                             let version_part = &line[version_start + 8..];
                             if let Some(version_end) = version_part.find('(') {
                                 let version = &version_part[..version_end];
-                                return format!("{} {}", shell_name, version);
+                                return Some(format!("{} {}", shell_name, version));
                             } else if let Some(version_end) = version_part.find(' ') {
                                 let version = &version_part[..version_end];
-                                return format!("{} {}", shell_name, version);
+                                return Some(format!("{} {}", shell_name, version));
                             }
                         }
                     }
@@ -441,7 +436,7 @@ impl SystemInfo {
                         // Parse version from "zsh 5.9 (x86_64-pc-linux-gnu)"
                         let parts: Vec<&str> = line.split_whitespace().collect();
                         if parts.len() >= 2 {
-                            return format!("{} {}", shell_name, parts[1]);
+                            return Some(format!("{} {}", shell_name, parts[1]));
                         }
                     }
                 }
@@ -452,7 +447,7 @@ impl SystemInfo {
                         // Parse version from "fish, version 3.6.1"
                         if let Some(version_start) = line.find("version ") {
                             let version = &line[version_start + 8..].trim();
-                            return format!("{} {}", shell_name, version);
+                            return Some(format!("{} {}", shell_name, version));
                         }
                     }
                 }
@@ -464,7 +459,7 @@ impl SystemInfo {
                         if line.contains("dash") && line.starts_with("ii") {
                             let parts: Vec<&str> = line.split_whitespace().collect();
                             if parts.len() >= 3 {
-                                return format!("{} {}", shell_name, parts[2]);
+                                return Some(format!("{} {}", shell_name, parts[2]));
                             }
                         }
                     }
@@ -479,7 +474,7 @@ impl SystemInfo {
                         for word in &words {
                             // Look for version-like patterns (e.g., "1.2.3", "5.9")
                             if word.chars().next().unwrap_or('a').is_ascii_digit() && word.contains('.') {
-                                return format!("{} {}", shell_name, word);
+                                return Some(format!("{} {}", shell_name, word));
                             }
                         }
                     }
@@ -487,95 +482,46 @@ impl SystemInfo {
             }
         }
         
-        shell_name
+        Some(shell_name)
     }
 
-    fn get_terminal() -> String {
-        // Check common terminal environment variables
-        let term_vars = ["TERM_PROGRAM", "TERMINAL_EMULATOR", "TERM"];
-        
-        for var in &term_vars {
+    pub(crate) fn get_terminal() -> Option<String> {
+        // Terminals that identify themselves; inside tmux TERM_PROGRAM names tmux instead
+        for var in ["TERM_PROGRAM", "TERMINAL_EMULATOR"] {
             if let Ok(value) = env::var(var) {
-                if !value.is_empty() && value != "xterm-256color" {
-                    // Handle special cases where terminal reports with prefix
-                    if value == "xterm-kitty" {
-                        return "kitty".to_string();
-                    }
-                    return value;
+                if !value.is_empty() && value != "tmux" {
+                    return Some(value);
                 }
             }
         }
-
-        // Try to detect from parent process chain
+        
         // Some terminals like Alacritty don't set specific env vars
-        if let Some(terminal) = Self::detect_terminal_from_process_tree() {
-            return terminal;
+        if let Some(terminal) = process::nearest(Role::Terminal) {
+            return Some(terminal.display_name());
+        }
+        
+        // TERM only names the terminfo entry, so it's the last resort
+        match env::var("TERM") {
+            Ok(term) if term == "xterm-kitty" => Some("kitty".to_string()),
+            Ok(term) if !term.is_empty() && term != "xterm-256color" => Some(term),
+            _ => None,
         }
-
-        "Unknown".to_string()
     }
     
-    fn detect_terminal_from_process_tree() -> Option<String> {
-        // Get current process ID
-        let current_pid = std::process::id();
-        
-        // Try to trace back through parent processes to find terminal
-        let mut pid = current_pid;
-        for _ in 0..10 { // Limit depth to avoid infinite loops
-            if let Ok(output) = Command::new("ps")
-                .args(&["-p", &pid.to_string(), "-o", "ppid=,comm="])
-                .output()
-            {
-                if let Ok(ps_output) = String::from_utf8(output.stdout) {
-                    let line = ps_output.trim();
-                    if line.is_empty() {
-                        break;
-                    }
-                    
-                    let parts: Vec<&str> = line.split_whitespace().collect();
-                    if parts.len() >= 2 {
-                        let parent_pid = parts[0];
-                        let comm = parts[1];
-                        
-                        // Check if this is a known terminal
-                        match comm {
-                            "alacritty" | "kitty" | "ghostty" | "wezterm" | 
-                            "gnome-terminal" | "konsole" | "xterm" | "urxvt" |
-                            "terminator" | "tilix" | "st" | "foot" => {
-                                return Some(comm.to_string());
-                            }
-                            _ => {}
-                        }
-                        
-                        // Continue with parent process
-                        if let Ok(ppid) = parent_pid.parse::<u32>() {
-                            if ppid == 1 || ppid == pid {
-                                break; // Reached init or circular reference
-                            }
-                            pid = ppid;
-                        } else {
-                            break;
-                        }
-                    } else {
-                        break;
-                    }
-                } else {
-                    break;
-                }
-            } else {
-                break;
-            }
+    pub(crate) fn get_multiplexer() -> Option<String> {
+        if let Some(multiplexer) = process::nearest(Role::Multiplexer) {
+            return Some(multiplexer.display_name());
         }
         
-        None
+        // Attached clients aren't ancestors of the shell; the session's variables remain
+        [("TMUX", "tmux"), ("ZELLIJ", "zellij"), ("STY", "screen")]
+            .into_iter()
+            .find(|(var, _)| env::var(var).is_ok_and(|value| !value.is_empty()))
+            .map(|(_, name)| name.to_string())
     }
 
-    fn get_terminal_with_version() -> String {
-        let terminal_name = Self::get_terminal();
-        
-        if terminal_name == "Unknown" {
-            return terminal_name;
-        }
+    pub(crate) fn get_terminal_with_version() -> Option<String> {
+        let terminal_name = Self::get_terminal()?;
         
         // Try to get version for common terminals
         match terminal_name.as_str() {
@@ -585,7 +531,7 @@ impl SystemInfo {
                         // Parse version from "kitty 0.32.2"
                         let parts: Vec<&str> = line.split_whitespace().collect();
                         if parts.len() >= 2 {
-                            return format!("{} {}", terminal_name, parts[1]);
+                            return Some(format!("{} {}", terminal_name, parts[1]));
                         }
                     }
                 }
@@ -596,7 +542,7 @@ impl SystemInfo {
                         // Parse version from "ghostty 1.0.0"
                         let parts: Vec<&str> = line.split_whitespace().collect();
                         if parts.len() >= 2 {
-                            return format!("{} {}", terminal_name, parts[1]);
+                            return Some(format!("{} {}", terminal_name, parts[1]));
                         }
                     }
                 }
@@ -607,7 +553,7 @@ impl SystemInfo {
                         // Parse version from "alacritty 0.13.2"
                         let parts: Vec<&str> = line.split_whitespace().collect();
                         if parts.len() >= 2 {
-                            return format!("{} {}", terminal_name, parts[1]);
+                            return Some(format!("{} {}", terminal_name, parts[1]));
                         }
                     }
                 }
@@ -620,7 +566,7 @@ impl SystemInfo {
                             let version_part = &line[version_start + 9..];
                             if let Some(version_end) = version_part.find(' ') {
                                 let version = &version_part[..version_end];
-                                return format!("GNOME Terminal {}", version);
+                                return Some(format!("GNOME Terminal {}", version));
                             }
                         }
                     }
@@ -632,7 +578,7 @@ impl SystemInfo {
                         if line.starts_with("konsole ") {
                             let parts: Vec<&str> = line.split_whitespace().collect();
                             if parts.len() >= 2 {
-                                return format!("{} {}", terminal_name, parts[1]);
+                                return Some(format!("{} {}", terminal_name, parts[1]));
                             }
                         }
                     }
@@ -644,7 +590,7 @@ impl SystemInfo {
                         // Parse version from "wezterm 20240203-110809-5046fc22"
                         let parts: Vec<&str> = line.split_whitespace().collect();
                         if parts.len() >= 2 {
-                            return format!("{} {}", terminal_name, parts[1]);
+                            return Some(format!("{} {}", terminal_name, parts[1]));
                         }
                     }
                 }
@@ -658,7 +604,7 @@ impl SystemInfo {
                         for word in &words {
                             // Look for version-like patterns (e.g., "1.2.3", "0.13.2")
                             if word.chars().next().unwrap_or('a').is_ascii_digit() && word.contains('.') {
-                                return format!("{} {}", terminal_name, word);
+                                return Some(format!("{} {}", terminal_name, word));
                             }
                         }
                     }
@@ -666,20 +612,89 @@ impl SystemInfo {
             }
         }
         
-        terminal_name
+        Some(terminal_name)
     }
 
-    fn get_cpu_info(sys: &System) -> String {
+    pub(crate) fn get_cpu_info(sys: &System) -> Fields {
         if let Some(cpu) = sys.cpus().first() {
-            let brand = cpu.brand().trim();
-            let cores = sys.cpus().len();
-            format!("{} ({} cores)", brand, cores)
+            Fields::new()
+                .with("name", cpu.brand().trim())
+                .with("cores", Value::count(sys.cpus().len()))
         } else {
-            "Unknown CPU".to_string()
+            Fields::new().with("name", Value::unavailable("no CPU reported"))
         }
     }
 
-    fn get_gpu_info() -> String {
+    pub(crate) fn get_gpu_info(cache: &CacheConfig) -> Vec<Fields> {
+        let gpus = gpu::gpus(cache);
+        if gpus.is_empty() {
+            return vec![Self::get_gpu_info_from_tools()];
+        }
+
+        // The proprietary driver exposes neither a marketing name nor VRAM and load in sysfs
+        let nvidia = if gpus.iter().any(|gpu| gpu.driver.as_deref() == Some("nvidia")) {
+            Self::query_nvidia_gpus()
+        } else {
+            Vec::new()
+        };
+
+        gpus.iter()
+            .map(|gpu| {
+                let mut fields = Fields::new()
+                    .with("name", gpu.name())
+                    .with_opt("vram_used", gpu.vram_total.and(gpu.vram_used).map(Value::bytes))
+                    .with_opt("vram_total", gpu.vram_total.map(Value::bytes))
+                    .with_opt("usage", gpu.busy_percent.map(|percent| Value::number(percent, Unit::Percent)))
+                    .with_opt("core_clock", gpu.core_clock.map(|hertz| Value::number(hertz, Unit::Hertz)))
+                    .with_opt("memory_clock", gpu.memory_clock.map(|hertz| Value::number(hertz, Unit::Hertz)))
+                    .with_opt("power", gpu.power.map(|watts| Value::number(watts, Unit::Watts)))
+                    .with_opt("driver", gpu.driver.clone())
+                    // Only worth pointing out when there is a choice
+                    .with_opt("primary", (gpus.len() > 1 && gpu.boot_vga).then_some("primary"));
+
+                let address = gpu.pci_slot.as_deref().map(gpu::bus_address);
+                if let Some((_, smi)) = nvidia.iter().find(|(bus, _)| Some(bus) == address.as_ref()) {
+                    for (name, value) in smi.iter() {
+                        fields.set(name, value.clone());
+                    }
+                }
+                fields
+            })
+            .collect()
+    }
+
+    // Fields of each card nvidia-smi knows about, keyed by bus address
+    fn query_nvidia_gpus() -> Vec<(String, Fields)> {
+        let query = "--query-gpu=pci.bus_id,name,memory.used,memory.total,utilization.gpu,clocks.gr,clocks.mem,power.draw";
+        let Some(output) = Self::run_command("nvidia-smi", &[query, "--format=csv,noheader,nounits"]) else {
+            return Vec::new();
+        };
+        // Unsupported fields read "[N/A]" or "[Not Supported]"
+        let number = |field: &str| field.trim().parse::<f64>().ok();
+        output
+            .lines()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+                if fields.len() != 8 {
+                    return None;
+                }
+                let mebibytes = |field: &str| number(field).map(|mb| Value::bytes(mb as u64 * 1024 * 1024));
+                let megahertz = |field: &str| number(field).map(|mhz| Value::number(mhz * 1_000_000.0, Unit::Hertz));
+                let smi = Fields::new()
+                    .with("name", fields[1])
+                    .with_opt("vram_used", mebibytes(fields[2]))
+                    .with_opt("vram_total", mebibytes(fields[3]))
+                    .with_opt("usage", number(fields[4]).map(|percent| Value::number(percent, Unit::Percent)))
+                    .with_opt("core_clock", megahertz(fields[5]))
+                    .with_opt("memory_clock", megahertz(fields[6]))
+                    .with_opt("power", number(fields[7]).map(|watts| Value::number(watts, Unit::Watts)));
+                Some((gpu::bus_address(fields[0]), smi))
+            })
+            .collect()
+    }
+
+    // Used when sysfs has no DRM devices, e.g. in containers
+    fn get_gpu_info_from_tools() -> Fields {
         // Try nvidia-smi first for NVIDIA cards (gives cleaner names)
         if let Some(output) = Self::run_command("nvidia-smi", &["--query-gpu=name", "--format=csv,noheader"]) {
             if let Some(gpu) = output.lines().next() {
@@ -691,15 +706,16 @@ impl SystemInfo {
                             let parts: Vec<&str> = vram_line.split(',').map(|s| s.trim()).collect();
                             if parts.len() == 2 {
                                 if let (Ok(used_mb), Ok(total_mb)) = (parts[0].parse::<u32>(), parts[1].parse::<u32>()) {
-                                    let used_gb = used_mb as f64 / 1024.0;
-                                    let total_gb = total_mb as f64 / 1024.0;
-                                    return format!("{} ({:.1}GB / {:.1}GB)", clean_name, used_gb, total_gb);
+                                    return Fields::new()
+                                        .with("name", clean_name)
+                                        .with("vram_used", Value::bytes(used_mb as u64 * 1024 * 1024))
+                                        .with("vram_total", Value::bytes(total_mb as u64 * 1024 * 1024));
                                 }
                             }
                         }
                     }
                     // Fallback to name only if VRAM query fails
-                    return clean_name.to_string();
+                    return Fields::new().with("name", clean_name);
                 }
             }
         }
@@ -712,17 +728,21 @@ impl SystemInfo {
                         let clean_gpu_name = Self::parse_gpu_name(gpu);
                         
                         // Try to get VRAM usage for AMD/Intel GPUs
-                        if let Some((used_gb, total_gb)) = Self::get_non_nvidia_vram_usage() {
-                            return format!("{} ({:.1}GB / {:.1}GB)", clean_gpu_name, used_gb, total_gb);
+                        if let Some((used, total)) = Self::get_non_nvidia_vram_usage() {
+                            let used = used.map_or_else(|| Value::unavailable("not reported by driver"), Value::bytes);
+                            return Fields::new()
+                                .with("name", clean_gpu_name)
+                                .with("vram_used", used)
+                                .with("vram_total", Value::bytes(total));
                         }
                         
-                        return clean_gpu_name;
+                        return Fields::new().with("name", clean_gpu_name);
                     }
                 }
             }
         }
 
-        "Unknown GPU".to_string()
+        Fields::new().with("name", Value::unavailable("no GPU detected"))
     }
 
     fn parse_gpu_name(raw_name: &str) -> String {
@@ -773,7 +793,7 @@ impl SystemInfo {
         result
     }
 
-    fn get_font_info() -> String {
+    pub(crate) fn get_font_info() -> Value {
         // Try to get font information from various terminal-specific methods
         
         // Debug: Check what terminal we're running in
@@ -806,9 +826,9 @@ impl SystemInfo {
                     
                     if let Some(family) = font_family {
                         if let Some(size) = font_size {
-                            return format!("{} ({}pt)", family, size);
+                            return Value::text(format!("{} ({}pt)", family, size));
                         } else {
-                            return family;
+                            return Value::text(family);
                         }
                     }
                 }
@@ -838,9 +858,9 @@ impl SystemInfo {
                     
                     if let Some(family) = font_family {
                         if let Some(size) = font_size {
-                            return format!("{} ({}pt)", family, size);
+                            return Value::text(format!("{} ({}pt)", family, size));
                         } else {
-                            return family;
+                            return Value::text(family);
                         }
                     }
                 }
@@ -860,7 +880,7 @@ impl SystemInfo {
                                     if start < end {
                                         let font = &line[start+1..end];
                                         if !font.is_empty() {
-                                            return Self::format_font_with_size(font);
+                                            return Value::text(Self::format_font_with_size(font));
                                         }
                                     }
                                 }
@@ -881,7 +901,7 @@ impl SystemInfo {
                     if let Some(font_output) = Self::run_command("gsettings", &["get", &profile_path, "font"]) {
                         let font = font_output.trim().trim_matches('\'').trim_matches('"');
                         if !font.is_empty() && font != "(null)" {
-                            return font.to_string();
+                            return Value::text(font.to_string());
                         }
                     }
                 }
@@ -891,7 +911,7 @@ impl SystemInfo {
             if let Some(output) = Self::run_command("gsettings", &["get", "org.gnome.Terminal.Legacy.Profile:/org/gnome/terminal/legacy/profiles:/:b1dcc9dd-5262-4d8d-a863-c897e6d979b9/", "font"]) {
                 let font = output.trim().trim_matches('\'').trim_matches('"');
                 if !font.is_empty() && font != "(null)" {
-                    return font.to_string();
+                    return Value::text(font.to_string());
                 }
             }
         }
@@ -900,7 +920,7 @@ impl SystemInfo {
         if let Some(output) = Self::run_command("dconf", &["read", "/org/gnome/terminal/legacy/profiles:/:b1dcc9dd-5262-4d8d-a863-c897e6d979b9/font"]) {
             let font = output.trim().trim_matches('\'').trim_matches('"');
             if !font.is_empty() && font != "(null)" {
-                return font.to_string();
+                return Value::text(font.to_string());
             }
         }
         
@@ -908,7 +928,7 @@ impl SystemInfo {
         if let Some(output) = Self::run_command("gsettings", &["get", "org.gnome.Terminal.Legacy.Profile:/org/gnome/terminal/legacy/profiles:/:b1dcc9dd-5262-4d8d-a863-c897e6d979b9/", "font"]) {
             let font = output.trim().trim_matches('\'').trim_matches('"');
             if !font.is_empty() && font != "(null)" {
-                return font.to_string();
+                return Value::text(font.to_string());
             }
         }
         
@@ -936,9 +956,9 @@ impl SystemInfo {
                 
                 if let Some(family) = font_family {
                     if let Some(size) = font_size {
-                        return format!("{} {}pt", family, size);
+                        return Value::text(format!("{} {}pt", family, size));
                     } else {
-                        return family;
+                        return Value::text(family);
                     }
                 }
             }
@@ -965,9 +985,9 @@ impl SystemInfo {
                 
                 if let Some(family) = font_family {
                     if let Some(size) = font_size {
-                        return format!("{} {}pt", family, size);
+                        return Value::text(format!("{} {}pt", family, size));
                     } else {
-                        return family;
+                        return Value::text(family);
                     }
                 }
             }
@@ -997,9 +1017,9 @@ impl SystemInfo {
                 
                 if let Some(family) = font_family {
                     if let Some(size) = font_size {
-                        return format!("{} {}pt", family, size);
+                        return Value::text(format!("{} {}pt", family, size));
                     } else {
-                        return family;
+                        return Value::text(family);
                     }
                 }
             }
@@ -1009,7 +1029,7 @@ impl SystemInfo {
         if let Some(output) = Self::run_command("gsettings", &["get", "org.gnome.desktop.interface", "monospace-font-name"]) {
             let font = output.trim().trim_matches('\'').trim_matches('"');
             if !font.is_empty() && font != "(null)" {
-                return font.to_string();
+                return Value::text(font.to_string());
             }
         }
         
@@ -1018,7 +1038,7 @@ impl SystemInfo {
             if let Some(font) = output.split(':').next() {
                 let font = font.trim();
                 if !font.is_empty() {
-                    return Self::format_font_with_size(font);
+                    return Value::text(Self::format_font_with_size(font));
                 }
             }
         }
@@ -1033,9 +1053,9 @@ impl SystemInfo {
         if std::env::var("WEZTERM_EXECUTABLE").is_ok() { env_vars.push("WEZTERM"); }
         
         if !env_vars.is_empty() {
-            format!("Unknown Font (detected: {})", env_vars.join(", "))
+            Value::unavailable(format!("font not found (detected: {})", env_vars.join(", ")))
         } else {
-            "Unknown Font (no terminal detected)".to_string()
+            Value::unavailable("no terminal detected")
         }
     }
     
@@ -1054,11 +1074,11 @@ impl SystemInfo {
         font.to_string()
     }
     
-    fn get_user_info() -> String {
+    pub(crate) fn get_user_info() -> Option<String> {
         // Get current username
         if let Ok(user) = std::env::var("USER") {
             if !user.is_empty() {
-                return user;
+                return Some(user);
             }
         }
         
@@ -1066,7 +1086,7 @@ impl SystemInfo {
         if let Some(output) = Self::run_command("whoami", &[]) {
             let user = output.trim();
             if !user.is_empty() {
-                return user.to_string();
+                return Some(user.to_string());
             }
         }
         
@@ -1076,20 +1096,28 @@ impl SystemInfo {
             if let Some(passwd_output) = Self::run_command("getent", &["passwd", uid]) {
                 if let Some(username) = passwd_output.split(':').next() {
                     if !username.is_empty() {
-                        return username.to_string();
+                        return Some(username.to_string());
                     }
                 }
             }
         }
         
-        "Unknown User".to_string()
+        None
     }
     
-    fn get_hostname_info() -> String {
+    pub(crate) fn get_hostname_info() -> Option<String> {
         // Try to get hostname from environment variable
         if let Ok(hostname) = std::env::var("HOSTNAME") {
             if !hostname.is_empty() {
-                return hostname;
+                return Some(hostname);
+            }
+        }
+        
+        // Kernel hostname, same as what the hostname command prints
+        if let Ok(content) = sysroot::read_to_string("/proc/sys/kernel/hostname") {
+            let hostname = content.trim();
+            if !hostname.is_empty() {
+                return Some(hostname.to_string());
             }
         }
         
@@ -1097,15 +1125,15 @@ impl SystemInfo {
         if let Some(output) = Self::run_command("hostname", &[]) {
             let hostname = output.trim();
             if !hostname.is_empty() {
-                return hostname.to_string();
+                return Some(hostname.to_string());
             }
         }
         
         // Try reading /etc/hostname
-        if let Ok(content) = std::fs::read_to_string("/etc/hostname") {
+        if let Ok(content) = sysroot::read_to_string("/etc/hostname") {
             let hostname = content.trim();
             if !hostname.is_empty() {
-                return hostname.to_string();
+                return Some(hostname.to_string());
             }
         }
         
@@ -1113,349 +1141,125 @@ impl SystemInfo {
         if let Some(output) = Self::run_command("uname", &["-n"]) {
             let hostname = output.trim();
             if !hostname.is_empty() {
-                return hostname.to_string();
+                return Some(hostname.to_string());
             }
         }
         
-        "Unknown Hostname".to_string()
+        None
     }
 
-    fn get_user_at_host_info() -> String {
-        let user = Self::get_user_info();
-        let hostname = Self::get_hostname_info();
-        format!("{}@{}", user, hostname)
+    pub(crate) fn get_user_at_host_info() -> Fields {
+        Fields::new()
+            .with("user", Value::text_or(Self::get_user_info(), "user not found"))
+            .with("host", Value::text_or(Self::get_hostname_info(), "hostname not set"))
     }
 
-    fn get_gpu_driver_info() -> String {
-        // Try to detect GPU driver from various sources
-        
-        // Method 1: Check loaded kernel modules for GPU drivers
-        if let Some(output) = Self::run_command("lsmod", &[]) {
-            let lines: Vec<&str> = output.lines().collect();
-            
-            // Check for NVIDIA drivers
-            for line in &lines {
-                if line.starts_with("nvidia ") {
-                    // Check if this is the open source driver by looking for nvidia-open packages
-                    let is_open_source = Self::is_nvidia_open_source_driver();
-                    
-                    // Try to get NVIDIA driver version
-                    if let Some(version_output) = Self::run_command("nvidia-smi", &["--query-gpu=driver_version", "--format=csv,noheader,nounits"]) {
-                        let version = version_output.trim();
-                        if !version.is_empty() {
-                            if is_open_source {
-                                return format!("NVIDIA (open source) {}", version);
-                            } else {
-                                return format!("NVIDIA (proprietary) {}", version);
-                            }
-                        }
-                    }
-                    
-                    if is_open_source {
-                        return "NVIDIA (open source)".to_string();
-                    } else {
-                        return "NVIDIA (proprietary)".to_string();
-                    }
-                }
-            }
-            
-            // Check for AMD drivers
-            for line in &lines {
-                if line.starts_with("amdgpu ") {
-                    return "AMDGPU (open source)".to_string();
-                } else if line.starts_with("radeon ") {
-                    return "Radeon (open source)".to_string();
-                }
-            }
-            
-            // Check for Intel drivers
-            for line in &lines {
-                if line.starts_with("i915 ") {
-                    return "Intel i915 (open source)".to_string();
-                } else if line.starts_with("xe ") {
-                    return "Intel Xe (open source)".to_string();
-                }
-            }
-            
-            // Check for other drivers
-            for line in &lines {
-                if line.starts_with("nouveau ") {
-                    return "Nouveau (open source)".to_string();
-                } else if line.starts_with("vmwgfx ") {
-                    return "VMware SVGA (open source)".to_string();
-                } else if line.starts_with("virtio_gpu ") {
-                    return "VirtIO GPU (open source)".to_string();
-                }
-            }
-        }
-        
-        // Method 2: Check /proc/driver/nvidia/version for NVIDIA
-        if let Ok(content) = std::fs::read_to_string("/proc/driver/nvidia/version") {
-            if let Some(line) = content.lines().next() {
-                // Parse version from line like "NVRM version: NVIDIA UNIX x86_64 Kernel Module  535.154.05"
-                if let Some(version_start) = line.rfind(" ") {
-                    let version = &line[version_start + 1..];
-                    if !version.is_empty() {
-                        return format!("NVIDIA (proprietary) {}", version);
-                    }
-                }
-                return "NVIDIA (proprietary)".to_string();
+    pub(crate) fn get_gpu_driver_info(cache: &CacheConfig) -> Vec<Fields> {
+        let mut modules: Vec<KernelModule> = Vec::new();
+        for module in gpu::gpus(cache).iter().filter_map(|gpu| gpu.module.as_ref()) {
+            if !modules.iter().any(|known| known.name == module.name) {
+                modules.push(module.clone());
             }
         }
-        
-        // Method 3: Check dmesg for GPU driver initialization (requires root or specific permissions)
-        if let Some(output) = Self::run_command("dmesg", &[]) {
-            let lines: Vec<&str> = output.lines().collect();
-            
-            // Look for NVIDIA driver messages
-            for line in lines.iter().rev().take(1000) { // Check last 1000 lines
-                if line.contains("NVIDIA") && (line.contains("driver") || line.contains("GPU")) {
-                    if line.contains("version") {
-                        // Try to extract version from dmesg
-                        let parts: Vec<&str> = line.split_whitespace().collect();
-                        for (i, part) in parts.iter().enumerate() {
-                            if *part == "version" && i + 1 < parts.len() {
-                                return format!("NVIDIA (proprietary) {}", parts[i + 1]);
-                            }
-                        }
-                    }
-                    return "NVIDIA (proprietary)".to_string();
-                }
-                
-                // Look for AMD driver messages
-                if line.contains("amdgpu") && line.contains("driver") {
-                    return "AMDGPU (open source)".to_string();
-                }
-                
-                // Look for Intel driver messages
-                if line.contains("i915") && line.contains("driver") {
-                    return "Intel i915 (open source)".to_string();
-                }
-            }
+        // Without DRM devices (containers, some VMs) a loaded GPU module is the best hint
+        if modules.is_empty() {
+            let known = ["nvidia", "amdgpu", "radeon", "i915", "xe", "nouveau"];
+            modules.extend(known.iter().find_map(|name| KernelModule::get(name)));
         }
-        
-        // Method 4: Check glxinfo for OpenGL driver info
-        if let Some(output) = Self::run_command("glxinfo", &["-B"]) {
-            for line in output.lines() {
-                if line.contains("OpenGL renderer string:") {
-                    if line.contains("NVIDIA") {
-                        return "NVIDIA (proprietary)".to_string();
-                    } else if line.contains("AMD") || line.contains("Radeon") {
-                        return "AMDGPU/Radeon (open source)".to_string();
-                    } else if line.contains("Intel") {
-                        return "Intel (open source)".to_string();
-                    }
-                }
-            }
-        }
-        
-        // Method 5: Check /sys/module for loaded GPU modules
-        let gpu_modules = [
-            ("/sys/module/nvidia", "NVIDIA (proprietary)"),
-            ("/sys/module/amdgpu", "AMDGPU (open source)"),
-            ("/sys/module/radeon", "Radeon (open source)"),
-            ("/sys/module/i915", "Intel i915 (open source)"),
-            ("/sys/module/xe", "Intel Xe (open source)"),
-            ("/sys/module/nouveau", "Nouveau (open source)"),
-        ];
-        
-        for (path, driver_name) in &gpu_modules {
-            if std::path::Path::new(path).exists() {
-                return driver_name.to_string();
-            }
-        }
-        
-        "Unknown Driver".to_string()
+
+        modules
+            .iter()
+            .map(|module| {
+                let name = match module.name.as_str() {
+                    "nvidia" => "NVIDIA",
+                    "amdgpu" => "AMDGPU",
+                    "radeon" => "Radeon",
+                    "i915" => "Intel i915",
+                    "xe" => "Intel Xe",
+                    "nouveau" => "Nouveau",
+                    "vmwgfx" => "VMware SVGA",
+                    "virtio_gpu" => "VirtIO GPU",
+                    name => name,
+                };
+                // Older NVIDIA drivers only report their version under /proc
+                let version = module.version.clone().or_else(|| {
+                    if module.name != "nvidia" {
+                        return None;
+                    }
+                    let content = sysroot::read_to_string("/proc/driver/nvidia/version").ok()?;
+                    // "NVRM version: NVIDIA UNIX x86_64 Kernel Module  535.154.05  ..."
+                    let line = content.lines().next()?;
+                    let version = line.split_once("Kernel Module")?.1.split_whitespace().next()?;
+                    Some(version.to_string())
+                });
+                Fields::new()
+                    .with("name", name)
+                    .with("type", if module.is_proprietary() { "proprietary" } else { "open source" })
+                    .with_opt("version", version)
+            })
+            .collect()
     }
 
-    fn get_memory_info(sys: &System) -> String {
-        let total_mem = sys.total_memory() / 1024 / 1024; // Convert to MB
-        let used_mem = sys.used_memory() / 1024 / 1024;
-        let total_gb = total_mem as f64 / 1024.0;
-        let used_gb = used_mem as f64 / 1024.0;
-        
-        format!("{:.1}GB / {:.1}GB ({:.0}%)", 
-                used_gb, total_gb, (used_mem as f64 / total_mem as f64) * 100.0)
+    pub(crate) fn get_memory_info(sys: &System) -> Fields {
+        let total_mem = sys.total_memory();
+        let used_mem = sys.used_memory();
+        
+        Fields::new()
+            .with("used", Value::bytes(used_mem))
+            .with("total", Value::bytes(total_mem))
+            .with("percent", Value::number((used_mem as f64 / total_mem as f64) * 100.0, Unit::Percent))
     }
 
-    fn get_disk_info(_sys: &System) -> String {
+    pub(crate) fn get_disk_info() -> Vec<Fields> {
         // Get specific important mountpoints with filesystem info
         let mut disk_info = Vec::new();
         
         // Always check root partition first
-        if let Some(info) = Self::get_disk_info_with_filesystem("/") {
-            if !info.is_empty() && info != "Unknown" {
-                disk_info.push(format!("/ {}", info));
-            }
-        }
-        
-        // Check /boot/efi if it exists
-        if let Some(info) = Self::get_disk_info_with_filesystem("/boot/efi") {
-            if !info.is_empty() && info != "Unknown" {
-                disk_info.push(format!("/boot {}", info));
-            }
-        }
-        
-        // Check /home only if it's on a separate device from /
-        if Self::is_separate_partition("/", "/home") {
-            if let Some(info) = Self::get_disk_info_with_filesystem("/home") {
-                if !info.is_empty() && info != "Unknown" {
-                    disk_info.push(format!("/home {}", info));
-                }
-            }
-        }
-        
-        // Join with bullet separator
-        if !disk_info.is_empty() {
-            disk_info.join(" • ")
-        } else {
-            // Fallback: try to get at least root filesystem info
-            Self::get_disk_info_with_filesystem("/").unwrap_or_else(|| "Unknown".to_string())
-        }
-    }
-    
-    fn get_single_disk_info(mount_point: &str) -> Option<String> {
-        // Simple wrapper for backward compatibility
-        Self::get_single_disk_info_with_device(mount_point).map(|(info, _)| info)
-    }
-    
-    fn get_disk_info_with_filesystem(mount_point: &str) -> Option<String> {
-        // Try multiple approaches to get disk info with filesystem type
-        
-        // Method 1: Use df -hT command with POSIX locale
-        if let Some(output) = Self::run_command_with_env("df", &["-hT", mount_point], &[("LC_ALL", "C")]) {
-            if let Some(result) = Self::parse_df_output_with_fs(&output, "LC_ALL=C") {
-                return Some(result);
-            }
+        if let Some(info) = mounts::at("/").and_then(Self::disk_usage_fields) {
+            disk_info.push(info.with("mount", "/"));
         }
         
-        // Method 2: Try df -hT without locale override
-        if let Some(output) = Self::run_command("df", &["-hT", mount_point]) {
-            if let Some(result) = Self::parse_df_output_with_fs(&output, "default") {
-                return Some(result);
-            }
+        // Check /boot/efi if it's mounted separately
+        if let Some(info) = mounts::at("/boot/efi").and_then(Self::disk_usage_fields) {
+            disk_info.push(info.with("mount", "/boot"));
         }
         
-        // Method 3: Try df -hT with explicit LANG=C
-        if let Some(output) = Self::run_command_with_env("df", &["-hT", mount_point], &[("LANG", "C")]) {
-            if let Some(result) = Self::parse_df_output_with_fs(&output, "LANG=C") {
-                return Some(result);
-            }
+        // Check /home only if it's on a separate filesystem from /
+        if let (Some(root), Some(home)) = (mounts::at("/"), mounts::containing("/home"))
+            && root.device_id != home.device_id
+            && let Some(info) = Self::disk_usage_fields(home)
+        {
+            disk_info.push(info.with("mount", "/home"));
         }
         
-        // Fallback: try without filesystem type
-        Self::get_single_disk_info(mount_point)
-    }
-    
-    fn is_separate_partition(mount1: &str, mount2: &str) -> bool {
-        // Check if two mount points are on separate devices/partitions
-        let device1 = Self::get_device_for_mountpoint(mount1);
-        let device2 = Self::get_device_for_mountpoint(mount2);
-        
-        match (device1, device2) {
-            (Some(d1), Some(d2)) => d1 != d2,
-            _ => false, // If we can't determine devices, assume they're the same
-        }
-    }
-    
-    fn get_device_for_mountpoint(mount_point: &str) -> Option<String> {
-        // Get the device name for a specific mount point using df
-        if let Some(output) = Self::run_command_with_env("df", &[mount_point], &[("LC_ALL", "C")]) {
-            for line in output.lines().skip(1) { // Skip header line
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() >= 1 {
-                    return Some(parts[0].to_string());
-                }
-            }
-        }
-        None
+        disk_info
     }
     
-    fn get_single_disk_info_with_device(mount_point: &str) -> Option<(String, String)> {
-        // Try multiple approaches to get disk info for a specific mount point
-        
-        // Method 1: Use df command with POSIX locale
-        if let Some(output) = Self::run_command_with_env("df", &["-h", mount_point], &[("LC_ALL", "C")]) {
-            if let Some((result, device)) = Self::parse_df_output_with_device(&output, "LC_ALL=C") {
-                return Some((result, device));
-            }
-        }
-        
-        // Method 2: Try df without locale override
-        if let Some(output) = Self::run_command("df", &["-h", mount_point]) {
-            if let Some((result, device)) = Self::parse_df_output_with_device(&output, "default") {
-                return Some((result, device));
-            }
-        }
-        
-        // Method 3: Try df with explicit LANG=C
-        if let Some(output) = Self::run_command_with_env("df", &["-h", mount_point], &[("LANG", "C")]) {
-            if let Some((result, device)) = Self::parse_df_output_with_device(&output, "LANG=C") {
-                return Some((result, device));
-            }
-        }
-        
-        None
+    // Usage of one mount, shared by the disk and dysk modules; inaccessible mounts are left out
+    fn disk_usage_fields(mount: &Mount) -> Option<Fields> {
+        let usage = match mount.usage() {
+            Err(Unmeasured::Inaccessible) => return None,
+            usage => usage,
+        };
+        Some(Fields::new()
+            .with("used", Self::usage_value(&usage, |usage| Value::bytes(usage.used)))
+            .with("total", Self::usage_value(&usage, |usage| Value::bytes(usage.total)))
+            .with("percent", Self::usage_value(&usage, |usage| Value::number(usage.percent(), Unit::Percent)))
+            .with("fs", mount.fs_type.as_str())
+            .with("free", Self::usage_value(&usage, |usage| Value::bytes(usage.available)))
+            .with("reserved", Self::usage_value(&usage, |usage| Value::bytes(usage.reserved)))
+            .with("device", mount.device()))
     }
-    
 
-    
-    fn parse_df_output_with_device(output: &str, _method: &str) -> Option<(String, String)> {
-        for line in output.lines().skip(1) { // Skip header line
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            
-            // Handle different df output formats
-            if parts.len() >= 6 {
-                // Standard format: Filesystem Size Used Avail Use% Mounted
-                let device = parts[0].to_string();
-                let total = parts[1];
-                let used = parts[2];
-                let usage = parts[4];
-                return Some((format!("{} / {} ({})", used, total, usage), device));
-            } else if parts.len() >= 5 {
-                // Alternative format: might have filesystem on separate line
-                let device = parts[0].to_string();
-                let total = parts[1];
-                let used = parts[2];
-                let usage = parts[4];
-                return Some((format!("{} / {} ({})", used, total, usage), device));
-            } else if parts.len() == 4 {
-                // Another format variation
-                let device = "unknown".to_string();
-                let total = parts[0];
-                let used = parts[1];
-                let usage = parts[2];
-                return Some((format!("{} / {} ({})", used, total, usage), device));
-            }
-        }
-        None
-    }
-    
-    fn parse_df_output_with_fs(output: &str, _method: &str) -> Option<String> {
-        for line in output.lines().skip(1) { // Skip header line
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            
-            // Handle df -hT output format: Filesystem Type Size Used Avail Use% Mounted
-            if parts.len() >= 7 {
-                // Standard format with filesystem type: Filesystem Type Size Used Avail Use% Mounted
-                let filesystem = parts[1];
-                let total = parts[2];
-                let used = parts[3];
-                let usage = parts[5];
-                return Some(format!("{} / {} ({}) [{}]", used, total, usage, filesystem));
-            } else if parts.len() >= 6 {
-                // Alternative format: might be missing some fields
-                let filesystem = parts[1];
-                let total = parts[2];
-                let used = parts[3];
-                let usage = parts[4];
-                return Some(format!("{} / {} ({}) [{}]", used, total, usage, filesystem));
-            }
+    // One figure of a mount's usage, or why it wasn't measured
+    fn usage_value(usage: &Result<Usage, Unmeasured>, value: impl Fn(&Usage) -> Value) -> Value {
+        match usage {
+            Ok(usage) => value(usage),
+            Err(unmeasured) => Value::unavailable(unmeasured.reason()),
         }
-        None
     }
 
-    fn get_resolution() -> String {
+    pub(crate) fn get_resolution() -> Option<String> {
         // Try xrandr for X11
         if let Some(output) = Self::run_command("xrandr", &["--current"]) {
             let lines: Vec<&str> = output.lines().collect();
@@ -1497,7 +1301,7 @@ impl SystemInfo {
                                     if part.contains("*") {
                                         let rate_str = part.replace("*", "").replace("+", "");
                                         if let Ok(rate) = rate_str.parse::<f32>() {
-                                            return format!("{} @ {:.0}Hz", resolution, rate);
+                                            return Some(format!("{} @ {:.0}Hz", resolution, rate));
                                         }
                                     }
                                 }
@@ -1507,7 +1311,7 @@ impl SystemInfo {
                 }
                 
                 // If no refresh rate found, return just resolution
-                return resolution;
+                return Some(resolution);
             }
         }
 
@@ -1531,9 +1335,9 @@ impl SystemInfo {
                     
                     if let Some(res) = resolution {
                         if let Some(rate) = refresh_rate {
-                            return format!("{} @ {:.0}Hz", res, rate);
+                            return Some(format!("{} @ {:.0}Hz", res, rate));
                         } else {
-                            return res;
+                            return Some(res);
                         }
                     }
                 }
@@ -1583,9 +1387,9 @@ impl SystemInfo {
                         
                         if let (Some(w), Some(h)) = (width, height) {
                             if let Some(r) = refresh {
-                                return format!("{}x{} @ {}Hz", w, h, r);
+                                return Some(format!("{}x{} @ {}Hz", w, h, r));
                             } else {
-                                return format!("{}x{}", w, h);
+                                return Some(format!("{}x{}", w, h));
                             }
                         }
                     }
@@ -1593,23 +1397,26 @@ impl SystemInfo {
             }
         }
 
-        "Unknown".to_string()
+        None
     }
 
-    fn get_battery_info() -> Option<String> {
+    pub(crate) fn get_battery_info() -> Option<Fields> {
         // Check /sys/class/power_supply for battery info
-        if let Ok(entries) = fs::read_dir("/sys/class/power_supply") {
-            for entry in entries.flatten() {
-                let path = entry.path();
+        if let Ok(entries) = sysroot::read_dir("/sys/class/power_supply") {
+            for path in entries {
                 if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
                     if name.starts_with("BAT") {
                         if let (Ok(capacity), Ok(status)) = (
-                            fs::read_to_string(path.join("capacity")),
-                            fs::read_to_string(path.join("status"))
+                            sysroot::read_to_string(path.join("capacity")),
+                            sysroot::read_to_string(path.join("status"))
                         ) {
-                            let capacity = capacity.trim();
-                            let status = status.trim();
-                            return Some(format!("{}% ({})", capacity, status));
+                            let percent = match capacity.trim().parse::<f64>() {
+                                Ok(percent) => Value::number(percent, Unit::Percent),
+                                Err(_) => Value::unavailable("unreadable capacity"),
+                            };
+                            return Some(Fields::new()
+                                .with("percent", percent)
+                                .with("status", status.trim()));
                         }
                     }
                 }
@@ -1618,69 +1425,100 @@ impl SystemInfo {
         None
     }
 
-    fn get_package_count() -> Option<String> {
-        // Try different package managers
-        let package_managers = [
-            ("dpkg", vec!["--get-selections"]),
-            ("rpm", vec!["-qa"]),
-            ("pacman", vec!["-Q"]),
-            ("emerge", vec!["--list-sets"]),
-            ("xbps-query", vec!["-l"]),
-        ];
+    pub(crate) fn get_package_count(config: &PackageCountsConfig) -> Vec<Fields> {
+        packages::native()
+            .iter()
+            .map(|count| {
+                let mut fields = Self::package_fields(count);
+                let wants = |kind: PackageBreakdown| config.breakdown.contains(&kind);
+                let mut counts: Vec<(PackageBreakdown, usize)> = Vec::new();
 
-        for (cmd, args) in &package_managers {
-            if let Some(output) = Self::run_command(cmd, args) {
-                let count = output.lines().count();
-                return Some(format!("{} ({})", count, cmd));
-            }
-        }
+                match count.manager {
+                    "pacman" => {
+                        if (wants(PackageBreakdown::Explicit) || wants(PackageBreakdown::Orphans))
+                            && let Some((explicit, orphans)) = packages::pacman_explicit_and_orphans()
+                        {
+                            counts.push((PackageBreakdown::Explicit, explicit));
+                            counts.push((PackageBreakdown::Orphans, orphans));
+                        }
+                        // Runs pacman, so only when asked for
+                        if wants(PackageBreakdown::Foreign)
+                            && let Some(foreign) = packages::pacman_foreign()
+                        {
+                            counts.push((PackageBreakdown::Foreign, foreign));
+                        }
+                    }
+                    "dpkg" => {
+                        if (wants(PackageBreakdown::Manual) || wants(PackageBreakdown::Auto))
+                            && let Some(automatic) = packages::apt_automatic()
+                        {
+                            counts.push((PackageBreakdown::Manual, count.count.saturating_sub(automatic)));
+                            counts.push((PackageBreakdown::Auto, automatic));
+                        }
+                    }
+                    _ => {}
+                }
 
-        None
+                // "1210 explicit, 38 foreign, 5 orphans", in the configured order
+                let mut details = Vec::new();
+                for kind in &config.breakdown {
+                    let Some(&(_, number)) = counts.iter().find(|(counted, _)| counted == kind) else {
+                        continue;
+                    };
+                    let (field, label) = match kind {
+                        PackageBreakdown::Explicit => ("explicit", "explicit"),
+                        PackageBreakdown::Foreign => ("foreign", "foreign"),
+                        PackageBreakdown::Orphans => ("orphans", if number == 1 { "orphan" } else { "orphans" }),
+                        PackageBreakdown::Manual => ("manual", "manual"),
+                        PackageBreakdown::Auto => ("auto", "auto"),
+                    };
+                    fields.set(field, Value::count(number));
+                    details.push(format!("{} {}", number, label));
+                }
+                fields.with_opt("details", (!details.is_empty()).then(|| Value::list(details)))
+            })
+            .collect()
+    }
+
+    fn package_fields(count: &packages::Count) -> Fields {
+        Fields::new()
+            .with("count", Value::count(count.count))
+            .with("manager", count.manager)
     }
 
-    fn get_flatpak_packages() -> Option<String> {
+    pub(crate) fn get_flatpak_packages() -> Option<Fields> {
         // Check if flatpak is installed and get package count
         if let Some(output) = Self::run_command("flatpak", &["list", "--app"]) {
             let count = output.lines().filter(|line| !line.trim().is_empty()).count();
             if count > 0 {
-                return Some(format!("{} (flatpak)", count));
+                return Some(Fields::new()
+                    .with("count", Value::count(count))
+                    .with("manager", "flatpak"));
             }
         }
         None
     }
 
-    fn get_combined_packages() -> Option<String> {
-        let mut package_parts = Vec::new();
-        
-        // Get main package manager count
-        if let Some(packages) = Self::get_package_count() {
-            package_parts.push(packages);
-        }
-        
-        // Get flatpak count
-        if let Some(flatpak) = Self::get_flatpak_packages() {
-            package_parts.push(flatpak);
-        }
-        
-        if !package_parts.is_empty() {
-            Some(package_parts.join(", "))
-        } else {
-            None
-        }
+    pub(crate) fn get_combined_packages(config: &PackageCountsConfig) -> Vec<Fields> {
+        // Distribution packages first, then nix, snap, Homebrew and flatpak
+        let mut entries = Self::get_package_count(config);
+        entries.extend(packages::additional().iter().map(Self::package_fields));
+        entries.extend(Self::get_flatpak_packages());
+        entries
     }
 
-    fn get_locale() -> String {
+    pub(crate) fn get_locale() -> Option<String> {
         env::var("LANG")
             .or_else(|_| env::var("LC_ALL"))
-            .unwrap_or_else(|_| "Unknown".to_string())
+            .ok()
     }
 
-    fn get_theme() -> String {
+    pub(crate) fn get_theme() -> Option<String> {
         // Try to get GTK theme
         if let Some(gtk_theme) = Self::run_command("gsettings", &["get", "org.gnome.desktop.interface", "gtk-theme"]) {
             let theme = gtk_theme.trim().trim_matches('\'').trim_matches('"');
             if !theme.is_empty() && theme != "Unknown" {
-                return theme.to_string();
+                return Some(theme.to_string());
             }
         }
         
@@ -1692,21 +1530,21 @@ impl SystemInfo {
             for line in kde_config.lines() {
                 if line.starts_with("ColorScheme=") {
                     if let Some(theme) = line.split('=').nth(1) {
-                        return theme.to_string();
+                        return Some(theme.to_string());
                     }
                 }
             }
         }
         
-        "Unknown".to_string()
+        None
     }
 
-    fn get_icons() -> String {
+    pub(crate) fn get_icons() -> Option<String> {
         // Try to get GTK icon theme
         if let Some(icon_theme) = Self::run_command("gsettings", &["get", "org.gnome.desktop.interface", "icon-theme"]) {
             let theme = icon_theme.trim().trim_matches('\'').trim_matches('"');
             if !theme.is_empty() && theme != "Unknown" {
-                return theme.to_string();
+                return Some(theme.to_string());
             }
         }
         
@@ -1718,16 +1556,67 @@ impl SystemInfo {
             for line in kde_config.lines() {
                 if line.starts_with("Theme=") {
                     if let Some(theme) = line.split('=').nth(1) {
-                        return theme.to_string();
+                        return Some(theme.to_string());
                     }
                 }
             }
         }
         
-        "Unknown".to_string()
+        None
     }
 
-    fn get_os_age() -> String {
+    pub(crate) fn get_last_update() -> Option<Fields> {
+        let upgrade = updates::last_upgrade();
+        let reboot = updates::pending_reboot(Self::get_kernel_version().as_deref());
+        if upgrade.is_none() && reboot.is_none() {
+            return None;
+        }
+
+        let ago = upgrade.as_ref().map(|upgrade| upgrade.time.elapsed().unwrap_or_default().as_secs());
+        let unknown = Value::unavailable("no upgrade in the package manager logs");
+        let reboot = reboot.map(|reboot| match reboot {
+            updates::Reboot::Kernel(kernel) => format!("kernel {}", kernel),
+            updates::Reboot::Requested(packages) if packages.is_empty() => "requested".to_string(),
+            updates::Reboot::Requested(packages) => packages.join(", "),
+        });
+        Some(
+            Fields::new()
+                .with("days", ago.map_or(unknown.clone(), |seconds| Value::count((seconds / (24 * 60 * 60)) as usize)))
+                .with("ago", ago.map_or(unknown, |seconds| Value::number(seconds as f64, Unit::Seconds)))
+                .with_opt("manager", upgrade.map(|upgrade| upgrade.manager))
+                .with_opt("reboot", reboot),
+        )
+    }
+
+    pub(crate) fn get_pending_updates(config: &UpdateCheckConfig) -> Option<Fields> {
+        // The check itself downloads package lists, far too slow for a greeting;
+        // this only reads its last result and starts a new one when that is old
+        let pending = updates::PendingUpdates::load();
+        let stale = pending.as_ref().is_none_or(|pending| pending.age().as_secs() >= config.interval);
+        // Recorded and sysroot runs describe another moment or machine
+        if stale && config.background && commands::is_live() && !sysroot::is_set() {
+            updates::spawn_refresh();
+        }
+
+        let pending = pending?;
+        let total: usize = pending.updates.iter().map(|available| available.count).sum();
+        let details: Vec<String> = pending
+            .updates
+            .iter()
+            .filter(|available| available.count > 0)
+            .map(|available| format!("{} {}", available.count, available.manager))
+            .collect();
+        let mut fields = Fields::new()
+            .with("total", Value::count(total))
+            .with_opt("details", (!details.is_empty()).then(|| Value::list(details)))
+            .with("age", Value::number(pending.age().as_secs() as f64, Unit::Seconds));
+        for available in &pending.updates {
+            fields.set(&available.manager, Value::count(available.count));
+        }
+        Some(fields)
+    }
+
+    pub(crate) fn get_os_age() -> Option<Fields> {
         use std::time::SystemTime;
         
         // Try different approaches to find OS installation date
@@ -1743,24 +1632,23 @@ impl SystemInfo {
         let mut candidates = Vec::new();
         
         for (path, priority) in &install_paths {
-            if let Ok(metadata) = fs::metadata(path) {
-                if let Ok(created) = metadata.created().or_else(|_| metadata.modified()) {
-                    if let Ok(duration) = SystemTime::now().duration_since(created) {
-                        let days = duration.as_secs() / (24 * 60 * 60);
-                        
-                        // Filter out unrealistic timestamps
-                        // Reject timestamps older than 10 years (3650 days) as likely corrupted
-                        // Reject timestamps from the future
-                        if days <= 3650 {
-                            candidates.push((days, *priority, *path));
-                        }
-                    }
+            if let Ok(metadata) = sysroot::metadata(path)
+                && let Ok(created) = metadata.created().or_else(|_| metadata.modified())
+                && let Ok(duration) = SystemTime::now().duration_since(created)
+            {
+                let days = duration.as_secs() / (24 * 60 * 60);
+                
+                // Filter out unrealistic timestamps
+                // Reject timestamps older than 10 years (3650 days) as likely corrupted
+                // Reject timestamps from the future
+                if days <= 3650 {
+                    candidates.push((days, *priority, *path));
                 }
             }
         }
         
         if candidates.is_empty() {
-            return "Unknown".to_string();
+            return None;
         }
         
         // Sort by priority (higher priority first), then by age (older first)
@@ -1771,157 +1659,140 @@ impl SystemInfo {
         // Select the best candidate
         let selected_days = candidates[0].0;
         
-        format!("{} days", selected_days)
+        Some(Fields::new().with("days", Value::count(selected_days as usize)))
     }
 
     fn run_command(command: &str, args: &[&str]) -> Option<String> {
-        match std::process::Command::new(command)
-            .args(args)
-            .output()
-        {
-            Ok(output) => {
-                if output.status.success() {
-                    Some(String::from_utf8_lossy(&output.stdout).to_string())
-                } else {
-                    None
-                }
-            }
-            Err(_) => None,
-        }
+        Self::run_command_with_env(command, args, &[])
     }
 
     fn run_command_with_env(command: &str, args: &[&str], env_vars: &[(&str, &str)]) -> Option<String> {
-        let mut cmd = std::process::Command::new(command);
-        cmd.args(args);
-        
-        for (key, value) in env_vars {
-            cmd.env(key, value);
-        }
-        
-        match cmd.output() {
-            Ok(output) => {
-                if output.status.success() {
-                    Some(String::from_utf8_lossy(&output.stdout).to_string())
-                } else {
-                    None
-                }
-            }
-            Err(_) => None,
-        }
+        commands::run(command, args, env_vars)
     }
 
-    fn get_cpu_temperature() -> String {
-        // Try to get CPU temperature from sensors command
-        if let Ok(output) = std::process::Command::new("sensors")
-            .output()
-        {
-            if output.status.success() {
-                let sensors_output = String::from_utf8_lossy(&output.stdout);
-                
-                // Look for k10temp (AMD) or coretemp (Intel) blocks
-                for line in sensors_output.lines() {
-                    let line = line.trim();
-                    
-                    // AMD: Look for Tctl temperature
-                    if line.starts_with("Tctl:") {
-                        if let Some(temp_part) = line.split_whitespace().nth(1) {
-                            if let Some(temp) = temp_part.strip_prefix("+").and_then(|t| t.strip_suffix("°C")) {
-                                return format!("{}°C", temp);
-                            }
-                        }
-                    }
-                    
-                    // Intel: Look for Core 0 temperature (first core as representative)
-                    if line.starts_with("Core 0:") {
-                        if let Some(temp_part) = line.split_whitespace().nth(2) {
-                            if let Some(temp) = temp_part.strip_prefix("+").and_then(|t| t.strip_suffix("°C")) {
-                                return format!("{}°C", temp);
-                            }
-                        }
-                    }
-                    
-                    // Generic: Look for Package id 0 (Intel)
-                    if line.starts_with("Package id 0:") {
-                        if let Some(temp_part) = line.split_whitespace().nth(3) {
-                            if let Some(temp) = temp_part.strip_prefix("+").and_then(|t| t.strip_suffix("°C")) {
-                                return format!("{}°C", temp);
-                            }
-                        }
-                    }
-                }
-            }
+    pub(crate) fn get_cpu_temperature(config: &TemperatureConfig) -> Option<f64> {
+        // A configured sensor is used as is, even if it has no reading right now
+        if let Some(selector) = &config.cpu {
+            return hwmon::select(selector).map(|sensor| sensor.celsius);
         }
         
-        "N/A".to_string()
+        // k10temp/zenpower (AMD) or coretemp (Intel), falling back to thermal zones
+        hwmon::primary(HwmonRole::Cpu).map(|sensor| sensor.celsius)
     }
 
-    fn get_gpu_temperature() -> String {
-        // Try NVIDIA first (nvidia-smi)
-        if let Ok(output) = std::process::Command::new("nvidia-smi")
-            .args(["--query-gpu=temperature.gpu", "--format=csv,noheader"])
-            .output()
-        {
-            if output.status.success() {
-                let temp_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
-                if !temp_str.is_empty() && temp_str != "N/A" {
-                    // Parse as float and format with one decimal place
-                    if let Ok(temp_float) = temp_str.parse::<f32>() {
-                        return format!("{:.1}°C", temp_float);
-                    }
-                    return format!("{}°C", temp_str);
-                }
-            }
+    pub(crate) fn get_gpu_temperature(config: &TemperatureConfig) -> Option<f64> {
+        if let Some(selector) = &config.gpu {
+            return hwmon::select(selector).map(|sensor| sensor.celsius);
         }
         
-        // Try AMD via sensors (amdgpu)
-        if let Ok(output) = std::process::Command::new("sensors")
-            .output()
+        // The proprietary NVIDIA driver doesn't register a hwmon device
+        if sysroot::exists("/sys/module/nvidia")
+            && let Some(output) = Self::run_command("nvidia-smi", &["--query-gpu=temperature.gpu", "--format=csv,noheader"])
+            && let Ok(temp) = output.trim().parse::<f64>()
         {
-            if output.status.success() {
-                let sensors_output = String::from_utf8_lossy(&output.stdout);
-                
-                // Look for amdgpu temperature
-                for line in sensors_output.lines() {
-                    let line = line.trim();
-                    
-                    if line.starts_with("edge:") || line.starts_with("junction:") {
-                        if let Some(temp_part) = line.split_whitespace().nth(1) {
-                            if let Some(temp) = temp_part.strip_prefix("+").and_then(|t| t.strip_suffix("°C")) {
-                                // Parse as float and format with one decimal place
-                                if let Ok(temp_float) = temp.parse::<f32>() {
-                                    return format!("{:.1}°C", temp_float);
-                                }
-                                return format!("{}°C", temp);
-                            }
-                        }
-                    }
-                }
-            }
+            return Some(temp);
         }
         
-        "N/A".to_string()
+        // amdgpu, radeon, nouveau, xe
+        hwmon::primary(HwmonRole::Gpu).map(|sensor| sensor.celsius)
     }
 
-    fn get_temp_combined() -> String {
-        let cpu_temp = Self::get_cpu_temperature();
-        let gpu_temp = Self::get_gpu_temperature();
+    pub(crate) fn get_temp_combined(config: &TemperatureConfig) -> Vec<Fields> {
+        let readings = config.combined.iter().map(|entry| match entry.as_str() {
+            "cpu" => ("CPU".to_string(), Self::get_cpu_temperature(config)),
+            "gpu" => ("GPU".to_string(), Self::get_gpu_temperature(config)),
+            selector => match hwmon::select(selector) {
+                Some(sensor) => (sensor.name(), Some(sensor.celsius)),
+                None => (selector.to_string(), None),
+            },
+        });
+        
+        // One entry per available temperature
+        let entries: Vec<Fields> = readings
+            .filter_map(|(label, temp)| {
+                let temp = temp?;
+                Some(Fields::new().with("label", label).with("temp", Value::number(temp, Unit::Celsius)))
+            })
+            .collect();
         
-        // Only show if both temperatures are available
-        if cpu_temp != "N/A" && gpu_temp != "N/A" {
-            format!("CPU {} • GPU {}", cpu_temp, gpu_temp)
-        } else if cpu_temp != "N/A" {
-            // Show only CPU if GPU is not available
-            format!("CPU {}", cpu_temp)
-        } else if gpu_temp != "N/A" {
-            // Show only GPU if CPU is not available
-            format!("GPU {}", gpu_temp)
+        if entries.is_empty() {
+            vec![Fields::new().with("temp", Value::unavailable("no sensors found"))]
         } else {
-            // Neither available
-            "N/A".to_string()
+            entries
         }
     }
 
-    fn get_terminal_shell_combined(show_versions: bool) -> String {
+    pub(crate) fn get_all_sensors() -> Vec<Fields> {
+        hwmon::temperatures()
+            .iter()
+            .map(|sensor| {
+                let celsius = |temp: f64| Value::number(temp, Unit::Celsius);
+                Fields::new()
+                    .with("name", sensor.name())
+                    .with("chip", sensor.chip.as_str())
+                    .with("label", sensor.label.as_str())
+                    .with("temp", celsius(sensor.celsius))
+                    .with_opt("max", sensor.max.map(celsius))
+                    .with_opt("crit", sensor.crit.map(celsius))
+            })
+            .collect()
+    }
+
+    pub(crate) fn get_fans_power() -> Vec<Fields> {
+        let mut entries = Vec::new();
+        
+        for fan in hwmon::fans() {
+            let label = match (&fan.label, fan.cools()) {
+                (Some(label), _) => label.clone(),
+                (None, Some(HwmonRole::Gpu)) => "GPU fan".to_string(),
+                (None, Some(HwmonRole::Cpu)) => "CPU fan".to_string(),
+                (None, _) => format!("Fan {}", fan.channel),
+            };
+            entries.push(Fields::new()
+                .with("label", label)
+                .with("value", Value::number(fan.value, Unit::Rpm)));
+        }
+        
+        for power in hwmon::power() {
+            // amdgpu calls its only sensor "PPT", which says little in a greeting
+            let label = match (power.chip.role(), &power.label) {
+                (HwmonRole::Gpu, _) => "GPU".to_string(),
+                (HwmonRole::Cpu, None) => "CPU".to_string(),
+                (_, Some(label)) => label.clone(),
+                (_, None) => power.chip.name.clone(),
+            };
+            entries.push(Fields::new()
+                .with("label", label)
+                .with("value", Value::number(power.value, Unit::Watts)));
+        }
+        
+        // Whole-system draw as seen by the battery while discharging; while charging
+        // power_now is the charge rate, and UPS or USB supplies report their own load
+        let batteries = sysroot::read_dir("/sys/class/power_supply").unwrap_or_default();
+        let read = |supply: &std::path::Path, attribute: &str| -> Option<String> {
+            Some(sysroot::read_to_string(supply.join(attribute)).ok()?.trim().to_string())
+        };
+        if let Some(battery) = batteries.iter().find(|supply| read(supply, "type").as_deref() == Some("Battery")) {
+            let number = |attribute: &str| -> Option<f64> { read(battery, attribute)?.parse().ok() };
+            let value = if read(battery, "status").as_deref() == Some("Discharging") {
+                // Some batteries only report current and voltage (µA × µV)
+                let microwatts = number("power_now").or_else(|| Some(number("current_now")? * number("voltage_now")? / 1_000_000.0));
+                match microwatts.filter(|microwatts| *microwatts > 0.0) {
+                    Some(microwatts) => Value::number(microwatts / 1_000_000.0, Unit::Watts),
+                    None => Value::unavailable("battery reports no power draw"),
+                }
+            } else {
+                Value::unavailable("battery not discharging")
+            };
+            entries.push(Fields::new()
+                .with("label", "System")
+                .with("value", value));
+        }
+        
+        entries
+    }
+
+    pub(crate) fn get_terminal_shell_combined(show_versions: bool) -> Vec<Fields> {
         let terminal = if show_versions {
             Self::get_terminal_with_version()
         } else {
@@ -1934,22 +1805,12 @@ impl SystemInfo {
             Self::get_shell()
         };
         
-        // Only show if both are available and not "Unknown"
-        if terminal != "Unknown" && shell != "Unknown" {
-            format!("{} • {}", terminal, shell)
-        } else if terminal != "Unknown" {
-            // Show only terminal if shell is not available
-            terminal
-        } else if shell != "Unknown" {
-            // Show only shell if terminal is not available
-            shell
-        } else {
-            // Neither available
-            "Unknown".to_string()
-        }
+        // One entry each, joined with the module separator; missing ones are skipped
+        [terminal, shell].into_iter().flatten().map(Fields::value).collect()
     }
 
-    fn get_non_nvidia_vram_usage() -> Option<(f64, f64)> {
+    /// VRAM as (used, total) bytes; most sources only report the total
+    fn get_non_nvidia_vram_usage() -> Option<(Option<u64>, u64)> {
         // Method 1: Try glxinfo for OpenGL memory info
         if let Some(output) = Self::run_command("glxinfo", &[]) {
             for line in output.lines() {
@@ -1959,8 +1820,7 @@ impl SystemInfo {
                     if let Some(memory_part) = line.split(":").nth(1) {
                         if let Some(mb_str) = memory_part.trim().split_whitespace().next() {
                             if let Ok(mb) = mb_str.parse::<u32>() {
-                                let total_gb = mb as f64 / 1024.0;
-                                return Some((0.0, total_gb)); // Can't get used from glxinfo
+                                return Some((None, mb as u64 * 1024 * 1024)); // Can't get used from glxinfo
                             }
                         }
                     }
@@ -1970,8 +1830,7 @@ impl SystemInfo {
                     if let Some(memory_part) = line.split(":").nth(1) {
                         if let Some(mb_str) = memory_part.trim().split_whitespace().next() {
                             if let Ok(mb) = mb_str.parse::<u32>() {
-                                let total_gb = mb as f64 / 1024.0;
-                                return Some((0.0, total_gb)); // Can't get used from glxinfo
+                                return Some((None, mb as u64 * 1024 * 1024)); // Can't get used from glxinfo
                             }
                         }
                     }
@@ -1980,9 +1839,8 @@ impl SystemInfo {
         }
         
         // Method 2: Try reading from /sys/class/drm/ for AMD GPUs
-        if let Ok(entries) = std::fs::read_dir("/sys/class/drm") {
-            for entry in entries.flatten() {
-                let path = entry.path();
+        if let Ok(entries) = sysroot::read_dir("/sys/class/drm") {
+            for path in entries {
                 if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
                     if name.starts_with("card") && !name.contains("-") {
                         // Try to read both used and total VRAM from AMD GPU
@@ -1990,31 +1848,27 @@ impl SystemInfo {
                         let vram_total_path = path.join("device/mem_info_vram_total");
                         
                         if let (Ok(used_content), Ok(total_content)) = (
-                            std::fs::read_to_string(&vram_used_path),
-                            std::fs::read_to_string(&vram_total_path)
+                            sysroot::read_to_string(&vram_used_path),
+                            sysroot::read_to_string(&vram_total_path)
                         ) {
                             if let (Ok(used_bytes), Ok(total_bytes)) = (
                                 used_content.trim().parse::<u64>(),
                                 total_content.trim().parse::<u64>()
                             ) {
-                                let used_gb = used_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
-                                let total_gb = total_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
-                                if total_gb > 0.1 { // Only return if we have significant VRAM
-                                    return Some((used_gb, total_gb));
+                                if total_bytes > 100 * 1024 * 1024 { // Only return if we have significant VRAM
+                                    return Some((Some(used_bytes), total_bytes));
                                 }
                             }
                         }
                         
                         // Fallback: Try to get total only from alternative paths
                         let vram_size_path = path.join("device/vram_size");
-                        if let Ok(vram_content) = std::fs::read_to_string(&vram_size_path) {
-                            if let Ok(vram_bytes) = vram_content.trim().parse::<u64>() {
-                                let total_gb = vram_bytes as f64 / (1024.0 * 1024.0 * 1024.0);
-                                if total_gb > 0.1 {
-                                    // Can't get used VRAM, so return 0 as used
-                                    return Some((0.0, total_gb));
-                                }
-                            }
+                        if let Ok(vram_content) = sysroot::read_to_string(&vram_size_path)
+                            && let Ok(vram_bytes) = vram_content.trim().parse::<u64>()
+                            && vram_bytes > 100 * 1024 * 1024
+                        {
+                            // Can't get used VRAM
+                            return Some((None, vram_bytes));
                         }
                     }
                 }
@@ -2038,14 +1892,13 @@ impl SystemInfo {
                             if size_str.ends_with("M") {
                                 if let Ok(mb) = size_str.trim_end_matches('M').parse::<u32>() {
                                     if mb >= 512 { // Only consider if >= 512MB (likely VRAM)
-                                        let total_gb = mb as f64 / 1024.0;
-                                        return Some((0.0, total_gb)); // Can't get used from lspci
+                                        return Some((None, mb as u64 * 1024 * 1024)); // Can't get used from lspci
                                     }
                                 }
                             } else if size_str.ends_with("G") {
                                 if let Ok(gb) = size_str.trim_end_matches('G').parse::<f64>() {
                                     if gb >= 0.5 {
-                                        return Some((0.0, gb)); // Can't get used from lspci
+                                        return Some((None, (gb * 1024.0 * 1024.0 * 1024.0) as u64)); // Can't get used from lspci
                                     }
                                 }
                             }
@@ -2063,188 +1916,144 @@ impl SystemInfo {
         None
     }
 
-    fn get_network_info() -> String {
-        // Try to get the primary network interface and its IP
-        if let Some(output) = Self::run_command("ip", &["route", "show", "default"]) {
-            // Extract default interface from "default via ... dev <interface>"
-            if let Some(line) = output.lines().next() {
-                if let Some(dev_pos) = line.find(" dev ") {
-                    let after_dev = &line[dev_pos + 5..];
-                    if let Some(interface) = after_dev.split_whitespace().next() {
-                        // Get IP address for this interface
-                        if let Some(ip_output) = Self::run_command("ip", &["addr", "show", interface]) {
-                            for ip_line in ip_output.lines() {
-                                let trimmed = ip_line.trim();
-                                if trimmed.starts_with("inet ") && !trimmed.contains("127.0.0.1") {
-                                    if let Some(ip_part) = trimmed.split_whitespace().nth(1) {
-                                        if let Some(ip) = ip_part.split('/').next() {
-                                            return format!("{} ({})", ip, interface);
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        
-                        // Fallback: show interface name only
-                        return format!("Connected ({})", interface);
-                    }
-                }
-            }
-        }
-        
-        // Alternative: Try to find any active interface with IP
-        if let Some(output) = Self::run_command("ip", &["addr", "show"]) {
-            let mut current_interface = String::new();
-            for line in output.lines() {
-                let trimmed = line.trim();
-                
-                // Check for interface line (starts with number)
-                if let Some(first_char) = trimmed.chars().next() {
-                    if first_char.is_ascii_digit() {
-                        if let Some(interface_part) = trimmed.split(':').nth(1) {
-                            current_interface = interface_part.trim().to_string();
-                        }
-                    }
-                }
-                
-                // Check for inet line with non-loopback IP
-                if trimmed.starts_with("inet ") && !trimmed.contains("127.0.0.1") && !current_interface.is_empty() {
-                    if let Some(ip_part) = trimmed.split_whitespace().nth(1) {
-                        if let Some(ip) = ip_part.split('/').next() {
-                            return format!("{} ({})", ip, current_interface);
-                        }
-                    }
-                }
-            }
-        }
-        
-        "Not connected".to_string()
+    pub(crate) fn get_network_info() -> Fields {
+        let route_v4 = net::default_route_v4();
+        let route_v6 = net::default_route_v6();
+        let addresses = net::addresses();
+
+        // The interface of the default route; without one, any interface with a usable address
+        let interface = route_v4
+            .as_ref()
+            .or(route_v6.as_ref())
+            .map(|route| route.interface.clone())
+            .or_else(|| addresses.iter().find(|address| address.is_global()).map(|address| address.interface.clone()));
+        let Some(interface) = interface else {
+            return Fields::new().with("address", Value::unavailable("not connected"));
+        };
+
+        let assigned = |v4: bool| -> Vec<String> {
+            addresses
+                .iter()
+                .filter(|address| address.interface == interface && address.is_global() && address.address.is_ipv4() == v4)
+                .map(|address| address.address.to_string())
+                .collect()
+        };
+        let (ipv4, ipv6) = (assigned(true), assigned(false));
+        let list = |addresses: &[String]| (!addresses.is_empty()).then(|| Value::list(addresses.to_vec()));
+        // Online through a route, but e.g. a VPN without an address of its own
+        let address = Value::text_or(ipv4.first().or(ipv6.first()).cloned(), "no global address on the interface");
+
+        let gateway = |route: Option<net::Route>| route.filter(|route| route.interface == interface)?.gateway.map(|gateway| gateway.to_string());
+        Fields::new()
+            .with("address", address)
+            .with("interface", interface.as_str())
+            .with_opt("ipv4", list(&ipv4))
+            .with_opt("ipv6", list(&ipv6))
+            .with_opt("gateway", gateway(route_v4))
+            .with_opt("gateway6", gateway(route_v6))
+            .with_opt("mtu", net::mtu(&interface).map(|mtu| Value::count(mtu as usize)))
+            .with_opt("speed", net::speed(&interface).map(|mbits| Value::number(mbits as f64 * 1e6, Unit::BitsPerSecond)))
     }
 
-    fn get_public_ip_info() -> String {
-        // Try multiple services to get public IP
-        let services = [
-            "ifconfig.me",
-            "ipinfo.io/ip",
-            "icanhazip.com",
-            "checkip.amazonaws.com"
-        ];
-        
-        for service in &services {
-            if let Some(output) = Self::run_command("curl", &["-s", "--max-time", "3", service]) {
-                let ip = output.trim();
-                if !ip.is_empty() && ip.chars().all(|c| c.is_ascii_digit() || c == '.') {
-                    // Try to get ISP info
-                    if let Some(isp) = Self::get_isp_info(&ip) {
-                        return format!("{} ({})", ip, isp);
-                    }
-                    return ip.to_string();
-                }
-            }
+    pub(crate) fn get_network_interfaces() -> Vec<Fields> {
+        let addresses = net::addresses();
+        let routes = [net::default_route_v4(), net::default_route_v6()];
+        let mut entries = Vec::new();
+
+        for interface in net::interfaces() {
+            // Host ends of container veth pairs come and go with containers and never have addresses
+            if !interface.up || interface.kind == net::Kind::Loopback || interface.name.starts_with("veth") {
+                continue;
+            }
+            let assigned = |v4: bool| -> Vec<String> {
+                addresses
+                    .iter()
+                    .filter(|address| address.interface == interface.name && address.is_global() && address.address.is_ipv4() == v4)
+                    .map(|address| address.address.to_string())
+                    .collect()
+            };
+            let (ipv4, ipv6) = (assigned(true), assigned(false));
+            let list = |addresses: &[String]| (!addresses.is_empty()).then(|| Value::list(addresses.to_vec()));
+            let is_default = routes.iter().flatten().any(|route| route.interface == interface.name);
+
+            entries.push(Fields::new()
+                .with("interface", interface.name.as_str())
+                .with("kind", interface.kind.name())
+                .with_opt("address", ipv4.first().or(ipv6.first()).cloned())
+                .with_opt("ipv4", list(&ipv4))
+                .with_opt("ipv6", list(&ipv6))
+                .with_opt("default", is_default.then_some("default"))
+                .with_opt("speed", net::speed(&interface.name).map(|mbits| Value::number(mbits as f64 * 1e6, Unit::BitsPerSecond))));
         }
-        
-        "Not available".to_string()
-    }
-    
-    fn get_isp_info(ip: &str) -> Option<String> {
-        // Try to get ISP information from ipinfo.io
-        let url = format!("ipinfo.io/{}/org", ip);
-        if let Some(output) = Self::run_command("curl", &["-s", "--max-time", "3", &url]) {
-            let org = output.trim();
-            if !org.is_empty() && !org.contains("error") {
-                // Clean up ISP name (remove AS numbers)
-                if let Some(space_pos) = org.find(' ') {
-                    let clean_name = &org[space_pos + 1..];
-                    if !clean_name.is_empty() {
-                        return Some(clean_name.to_string());
-                    }
-                }
-                return Some(org.to_string());
-            }
+
+        if let Some(resolver) = net::resolver()
+            && !resolver.nameservers.is_empty()
+        {
+            entries.push(Fields::new()
+                .with("dns", Value::list(resolver.nameservers))
+                .with_opt("search", (!resolver.search.is_empty()).then(|| Value::list(resolver.search))));
         }
-        None
+
+        entries
     }
 
-    fn get_dysk_info() -> String {
-        // Get all mounted filesystems using df command
-        let mut mount_info = Vec::new();
-        
-        // Use df to get all mounted filesystems with human-readable sizes
-        if let Some(output) = Self::run_command_with_env("df", &["-hT"], &[("LC_ALL", "C")]) {
-            for line in output.lines().skip(1) { // Skip header line
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() >= 7 {
-                    let device = parts[0];
-                    let filesystem = parts[1];
-                    let total = parts[2];
-                    let used = parts[3];
-                    let _available = parts[4];
-                    let usage_percent = parts[5];
-                    let mount_point = parts[6];
-                    
-                    // Skip pseudo filesystems and special mounts
-                    if Self::should_include_in_dysk(device, filesystem, mount_point) {
-                        // Parse usage percentage
-                        let usage_num = usage_percent.trim_end_matches('%')
-                            .parse::<u32>()
-                            .unwrap_or(0);
-                        
-                        // Create progress bar
-                        let progress_bar = Self::create_progress_bar(usage_num);
-                        
-                        // Clean device name (remove /dev/ prefix)
-                        let clean_device = device.strip_prefix("/dev/").unwrap_or(device);
-                        
-                        // Format the drive info in the new order:
-                        // Progress Bar, Percent (3 chars), Device Name, Usage (fixed width), Filesystem, Mount Point
-                        let drive_info = format!(
-                            "{} {:>3}% {} {:>4}/{:<4} [{}] {}",
-                            progress_bar,
-                            usage_num,
-                            clean_device,
-                            used,
-                            total,
-                            filesystem,
-                            mount_point
-                        );
-                        
-                        mount_info.push(drive_info);
-                    }
-                }
-            }
-        }
-        
-        // Also check for additional mounted drives that might be missed
-        if let Some(output) = Self::run_command("mount", &[]) {
-            for line in output.lines() {
-                if line.contains(" on ") && line.contains(" type ") {
-                    let parts: Vec<&str> = line.split(" on ").collect();
-                    if parts.len() >= 2 {
-                        let device = parts[0].trim();
-                        let rest = parts[1];
-                        if let Some(type_pos) = rest.find(" type ") {
-                            let mount_point = rest[..type_pos].trim();
-                            
-                            // Check if this is a removable/temporary mount we haven't seen
-                            if Self::is_temporary_mount(device, mount_point) &&
-                               !mount_info.iter().any(|info| info.contains(mount_point)) {
-                                
-                                // Get disk usage for this mount
-                                if let Some(usage_info) = Self::get_disk_usage_for_mount(mount_point) {
-                                    mount_info.push(usage_info);
-                                }
-                            }
-                        }
-                    }
-                }
+    pub(crate) fn get_public_ip_info(config: &PublicIpLookupConfig) -> Fields {
+        // A live lookup would report this machine's address, not the snapshot's;
+        // replayed bundles carry the recorded responses
+        if sysroot::is_set() && commands::is_live() {
+            return Fields::new().with("address", Value::unavailable("not looked up for a sysroot"));
+        }
+        let ipv4 = if config.ipv4 { public_ip::lookup(config, false) } else { None };
+        let ipv6 = if config.ipv6 { public_ip::lookup(config, true) } else { None };
+        // ISP and location come from whichever lookup provided them
+        let Some(primary) = ipv4.as_ref().or(ipv6.as_ref()) else {
+            return Fields::new().with("address", Value::unavailable("no lookup service reachable"));
+        };
+        let detail = |field: fn(&public_ip::Lookup) -> Option<String>| {
+            ipv4.as_ref().and_then(field).or_else(|| ipv6.as_ref().and_then(field))
+        };
+
+        Fields::new()
+            .with("address", primary.address.to_string())
+            .with_opt("ipv4", ipv4.as_ref().map(|lookup| lookup.address.to_string()))
+            .with_opt("ipv6", ipv6.as_ref().map(|lookup| lookup.address.to_string()))
+            .with_opt("isp", detail(|lookup| lookup.isp.clone()))
+            .with_opt("asn", detail(|lookup| lookup.asn.clone()))
+            .with_opt("country", detail(|lookup| lookup.country.clone()))
+    }
+
+    pub(crate) fn get_dysk_info() -> Vec<Fields> {
+        let mut mount_info: Vec<(&str, Fields)> = Vec::new();
+        
+        for mount in mounts::table() {
+            // Skip pseudo filesystems and special mounts, but keep removable drives
+            if !Self::should_include_in_dysk(&mount.source, &mount.fs_type, &mount.mount_point)
+                && !Self::is_temporary_mount(&mount.source, &mount.mount_point) {
+                continue;
+            }
+            let usage = match mount.usage() {
+                Err(Unmeasured::Inaccessible) => continue,
+                usage => usage,
+            };
+            
+            let fields = Fields::new()
+                .with("bar", Self::usage_value(&usage, |usage| Self::create_progress_bar(usage.percent().round() as u32).into()))
+                .with("percent", Self::usage_value(&usage, |usage| Value::number(usage.percent(), Unit::Percent)))
+                .with("device", mount.device())
+                .with("used", Self::usage_value(&usage, |usage| Value::bytes(usage.used)))
+                .with("total", Self::usage_value(&usage, |usage| Value::bytes(usage.total)))
+                .with("fs", mount.fs_type.as_str())
+                .with("mount", mount.mount_point.as_str())
+                .with("free", Self::usage_value(&usage, |usage| Value::bytes(usage.available)));
+            
+            // A later mount on the same point hides the earlier one
+            match mount_info.iter_mut().find(|(mount_point, _)| *mount_point == mount.mount_point) {
+                Some(entry) => entry.1 = fields,
+                None => mount_info.push((&mount.mount_point, fields)),
             }
         }
         
-        if mount_info.is_empty() {
-            "No mounted drives found".to_string()
-        } else {
-            mount_info.join("\n")
-        }
+        mount_info.into_iter().map(|(_, fields)| fields).collect()
     }
     
     fn should_include_in_dysk(device: &str, filesystem: &str, mount_point: &str) -> bool {
@@ -2306,42 +2115,6 @@ impl SystemInfo {
         )
     }
     
-    fn get_disk_usage_for_mount(mount_point: &str) -> Option<String> {
-        if let Some(output) = Self::run_command_with_env("df", &["-hT", mount_point], &[("LC_ALL", "C")]) {
-            for line in output.lines().skip(1) {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() >= 7 {
-                    let device = parts[0];
-                    let filesystem = parts[1];
-                    let total = parts[2];
-                    let used = parts[3];
-                    let usage_percent = parts[5];
-                    
-                    let usage_num = usage_percent.trim_end_matches('%')
-                        .parse::<u32>()
-                        .unwrap_or(0);
-                    
-                    let progress_bar = Self::create_progress_bar(usage_num);
-                    
-                    // Clean device name (remove /dev/ prefix)
-                    let clean_device = device.strip_prefix("/dev/").unwrap_or(device);
-                    
-                    return Some(format!(
-                        "{} {:>3}% {} {:>4}/{:<4} [{}] {}",
-                        progress_bar,
-                        usage_num,
-                        clean_device,
-                        used,
-                        total,
-                        filesystem,
-                        mount_point
-                    ));
-                }
-            }
-        }
-        None
-    }
-    
     fn create_progress_bar(usage_percent: u32) -> String {
         let bar_length = 10;
         let filled_length = (usage_percent * bar_length / 100).min(bar_length);
@@ -2360,56 +2133,4 @@ impl SystemInfo {
                 fill_char.repeat(filled_length as usize),
                 empty_char.repeat(empty_length as usize))
     }
-    
-    fn is_nvidia_open_source_driver() -> bool {
-        // Check for NVIDIA open source driver packages
-        // This works for various distributions that have nvidia-open packages
-        
-        // Method 1: Check pacman (Arch/CachyOS/Vanilla Arch)
-        if let Some(output) = Self::run_command("pacman", &["-Q"]) {
-            // Search for any package containing "nvidia-open" in its name
-            for line in output.lines() {
-                if line.contains("nvidia-open") {
-                    return true;
-                }
-            }
-        }
-        
-        // Method 2: Check dpkg (Debian/Ubuntu)
-        if let Some(output) = Self::run_command("dpkg", &["-l"]) {
-            // Search for any package containing "nvidia-open" in its name
-            for line in output.lines() {
-                if line.contains("nvidia-open") {
-                    return true;
-                }
-            }
-        }
-        
-        // Method 3: Check rpm (Red Hat/Fedora)
-        if let Some(output) = Self::run_command("rpm", &["-qa"]) {
-            // Search for any package containing "nvidia-open" in its name
-            for line in output.lines() {
-                if line.contains("nvidia-open") {
-                    return true;
-                }
-            }
-        }
-        
-        // Method 4: Check for open source driver files
-        if std::path::Path::new("/usr/lib/modules").exists() {
-            if let Ok(entries) = std::fs::read_dir("/usr/lib/modules") {
-                for entry in entries.flatten() {
-                    let path = entry.path();
-                    if path.is_dir() {
-                        let nvidia_open_path = path.join("kernel/drivers/gpu/drm/nvidia-drm-open.ko");
-                        if nvidia_open_path.exists() {
-                            return true;
-                        }
-                    }
-                }
-            }
-        }
-        
-        false
-    }
 }
@@ -0,0 +1,144 @@
+use unicode_width::UnicodeWidthStr;
+use crate::value::{Fields, Value};
+
+/// Render a module format string against the fields of one entry.
+///
+/// Supported syntax:
+/// - `{name}` inserts a field, `{name:>6}` / `{name:<6}` pads it to a width
+/// - `{?name}...{?}` only renders the section if the field is available,
+///   `{?!name}...{?}` only if it is missing or unavailable; sections may be nested
+/// - `{{` and `}}` produce literal braces
+///
+/// Unknown and unavailable fields render as empty strings; lists are joined
+/// with `list_separator`.
+pub fn render(template: &str, fields: &Fields, list_separator: &str) -> String {
+    let mut output = String::new();
+    // One entry per open conditional section
+    let mut sections: Vec<bool> = Vec::new();
+    let mut rest = template;
+
+    while let Some(pos) = rest.find(['{', '}']) {
+        let visible = sections.iter().all(|shown| *shown);
+        let (text, tail) = rest.split_at(pos);
+        if visible {
+            output.push_str(text);
+        }
+
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            if visible {
+                output.push_str(&tail[..1]);
+            }
+            rest = &tail[2..];
+            continue;
+        }
+
+        let end = match tail.find('}') {
+            Some(end) if tail.starts_with('{') => end,
+            _ => {
+                // Stray brace, keep it as text
+                if visible {
+                    output.push_str(&tail[..1]);
+                }
+                rest = &tail[1..];
+                continue;
+            }
+        };
+
+        let tag = &tail[1..end];
+        rest = &tail[end + 1..];
+
+        if tag == "?" {
+            sections.pop();
+        } else if let Some(name) = tag.strip_prefix("?!") {
+            sections.push(!fields.has(name));
+        } else if let Some(name) = tag.strip_prefix('?') {
+            sections.push(fields.has(name));
+        } else if visible {
+            output.push_str(&substitute(tag, fields, list_separator));
+        }
+    }
+
+    if sections.iter().all(|shown| *shown) {
+        output.push_str(rest);
+    }
+
+    output
+}
+
+fn substitute(tag: &str, fields: &Fields, list_separator: &str) -> String {
+    let (name, spec) = match tag.split_once(':') {
+        Some((name, spec)) => (name, spec),
+        None => (tag, ""),
+    };
+    let value = match fields.get(name) {
+        Some(Value::List(items)) => items.iter().map(Value::to_string).collect::<Vec<_>>().join(list_separator),
+        Some(value) => value.to_string(),
+        None => String::new(),
+    };
+
+    let (right_align, width) = match spec.strip_prefix('>') {
+        Some(width) => (true, width),
+        None => (false, spec.strip_prefix('<').unwrap_or(spec)),
+    };
+    let padding = width
+        .parse::<usize>()
+        .unwrap_or(0)
+        .saturating_sub(value.width());
+
+    if right_align {
+        format!("{}{}", " ".repeat(padding), value)
+    } else {
+        format!("{}{}", value, " ".repeat(padding))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::{Memory, Module};
+    use crate::value::Unit;
+
+    fn fields() -> Fields {
+        Fields::new()
+            .with("name", "eth0")
+            .with("address", "192.0.2.1")
+            .with("gateway", Value::unavailable("no default route"))
+    }
+
+    #[test]
+    fn substitutes_and_pads_fields() {
+        assert_eq!(render("{name} {address}", &fields(), ", "), "eth0 192.0.2.1");
+        assert_eq!(render("[{name:<6}][{name:>6}]", &fields(), ", "), "[eth0  ][  eth0]");
+        assert_eq!(render("{unknown}{gateway}", &fields(), ", "), "");
+    }
+
+    #[test]
+    fn renders_sections_by_availability() {
+        let template = "{name}{?gateway} via {gateway}{?}{?!gateway} (no gateway){?}";
+        assert_eq!(render(template, &fields(), ", "), "eth0 (no gateway)");
+        assert_eq!(render("{?address}{?name}{name}: {address}{?}{?}", &fields(), ", "), "eth0: 192.0.2.1");
+        assert_eq!(render("{?gateway}{?name}{name}{?}!{?}", &fields(), ", "), "");
+    }
+
+    #[test]
+    fn joins_lists_with_the_separator() {
+        let fields = fields().with("dns", Value::list(["10.8.0.1", "1.1.1.1"]));
+        assert_eq!(render("DNS {dns}", &fields, ", "), "DNS 10.8.0.1, 1.1.1.1");
+        assert_eq!(render("{?dns}{dns}{?}", &fields, " • "), "10.8.0.1 • 1.1.1.1");
+    }
+
+    #[test]
+    fn renders_the_default_memory_line() {
+        let fields = Fields::new()
+            .with("used", Value::bytes(576_000_000))
+            .with("total", Value::bytes(16_642_998_272))
+            .with("percent", Value::number(3.5, Unit::Percent));
+        assert_eq!(render(Memory.default_format(), &fields, ", "), "549MB / 15.5GB (4%)");
+    }
+
+    #[test]
+    fn keeps_escaped_and_stray_braces() {
+        assert_eq!(render("{{{name}}}", &fields(), ", "), "{eth0}");
+        assert_eq!(render("a } b {", &fields(), ", "), "a } b {");
+    }
+}
@@ -0,0 +1,464 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::env;
+use std::fs;
+use std::os::unix::fs::symlink;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use crate::cache;
+use crate::commands;
+use crate::sysroot;
+
+const MODULE_TREES: &[&str] = &["/usr/lib/modules", "/lib/modules"];
+
+/// The most recent upgrade a package manager logged
+#[derive(Debug, Clone)]
+pub struct Upgrade {
+    pub manager: &'static str,
+    pub time: SystemTime,
+}
+
+// Time of the last upgrade in one manager's log, if it has one
+type Source = fn() -> Option<SystemTime>;
+
+// Pending updates of one manager, or `None` if it isn't in use
+type Counter = fn() -> Option<usize>;
+
+/// The newest upgrade found in the pacman, dpkg/apt and dnf logs
+pub fn last_upgrade() -> Option<Upgrade> {
+    let sources: [(&str, Source); 4] = [
+        ("pacman", pacman),
+        ("dpkg", dpkg),
+        // Unattended upgrades and `apt upgrade` both log here; dpkg.log is rotated weekly
+        ("apt", apt),
+        ("dnf", dnf),
+    ];
+    sources
+        .iter()
+        .filter_map(|&(manager, source)| Some(Upgrade { manager, time: source()? }))
+        .max_by_key(|upgrade| upgrade.time)
+}
+
+// "[2024-05-01T12:34:56+0200] [ALPM] upgraded firefox (125.0-1 -> 125.0.3-1)"; before
+// pacman 5.1 the timestamp read "[2019-01-01 12:34]"
+fn pacman() -> Option<SystemTime> {
+    let log = sysroot::read_to_string("/var/log/pacman.log").ok()?;
+    log.lines()
+        .rev()
+        .filter(|line| line.contains("] [ALPM] upgraded "))
+        .find_map(|line| parse_time(line.strip_prefix('[')?.split(']').next()?))
+}
+
+// "2024-05-01 12:34:56 upgrade libc6:amd64 2.36-9 2.36-9+deb12u4"
+fn dpkg() -> Option<SystemTime> {
+    let log = sysroot::read_to_string("/var/log/dpkg.log").ok()?;
+    log.lines()
+        .rev()
+        .filter(|line| line.split_whitespace().nth(2) == Some("upgrade"))
+        .find_map(|line| parse_time(line.get(..19)?))
+}
+
+// One stanza per apt run: "Start-Date: 2024-05-01  12:34:19", "Upgrade: ...", "End-Date: ..."
+fn apt() -> Option<SystemTime> {
+    let log = sysroot::read_to_string("/var/log/apt/history.log").ok()?;
+    log.split("\n\n")
+        .filter(|stanza| stanza.lines().any(|line| line.starts_with("Upgrade:")))
+        .filter_map(|stanza| {
+            let date = |key: &str| stanza.lines().find_map(|line| line.strip_prefix(key));
+            parse_time(date("End-Date:").or_else(|| date("Start-Date:"))?)
+        })
+        .max()
+}
+
+fn dnf() -> Option<SystemTime> {
+    // "2024-05-01T12:34:56+0200 SUBDEBUG Upgrade: kernel-6.8.9-300.fc40.x86_64"
+    if let Ok(log) = sysroot::read_to_string("/var/log/dnf.rpm.log") {
+        return log
+            .lines()
+            .rev()
+            .filter(|line| line.contains(" Upgrade: ") || line.contains(" Upgraded: "))
+            .find_map(|line| parse_time(line.split_whitespace().next()?));
+    }
+
+    // The history itself is SQLite, so ask dnf. Its table lists the newest transaction first:
+    // "    12 | upgrade          | 2024-05-01 12:34 | I, U           |   50   "
+    if !sysroot::exists("/var/lib/dnf") {
+        return None;
+    }
+    let history = commands::run("dnf", &["history", "list"], &[])?;
+    history.lines().find_map(|line| {
+        let columns: Vec<&str> = line.split('|').map(str::trim).collect();
+        let actions = columns.get(3)?;
+        let upgraded = actions.split(", ").any(|action| action == "U" || action == "Upgrade");
+        if upgraded { parse_time(columns.get(2)?) } else { None }
+    })
+}
+
+// "2024-05-01T12:34:56+0200", "2024-05-01T10:34:56Z", "2024-05-01  12:34:56" or
+// "2024-05-01 12:34". pacman and dnf write ISO timestamps with an offset (dnf in
+// UTC); dpkg and apt write the local time without one.
+fn parse_time(timestamp: &str) -> Option<SystemTime> {
+    let timestamp = timestamp.trim();
+    // Date and time take the first 16 to 19 characters; anything after the seconds is the offset
+    let (datetime, offset) = match timestamp.get(16..) {
+        Some(rest) => {
+            let seconds_end = rest.find(|c: char| !(c == ':' || c.is_ascii_digit())).map_or(timestamp.len(), |end| 16 + end);
+            timestamp.split_at(seconds_end)
+        }
+        None => (timestamp, ""),
+    };
+    let numbers: Vec<i64> = datetime
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|part| !part.is_empty())
+        .take(6)
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+    if numbers.len() < 5 {
+        return None;
+    }
+    let [year, month, day, hour, minute] = [numbers[0], numbers[1], numbers[2], numbers[3], numbers[4]];
+    let second = numbers.get(5).copied().unwrap_or(0);
+
+    let seconds = match parse_offset(offset.trim()) {
+        Some(offset) => days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second - offset,
+        // SAFETY: mktime only reads and normalizes the zeroed tm it is given
+        None => unsafe {
+            let mut time: libc::tm = std::mem::zeroed();
+            time.tm_year = (year - 1900) as i32;
+            time.tm_mon = (month - 1) as i32;
+            time.tm_mday = day as i32;
+            time.tm_hour = hour as i32;
+            time.tm_min = minute as i32;
+            time.tm_sec = second as i32;
+            // Let mktime work out whether daylight saving time was in effect
+            time.tm_isdst = -1;
+            libc::mktime(&mut time) as i64
+        },
+    };
+    (seconds >= 0).then(|| UNIX_EPOCH + Duration::from_secs(seconds as u64))
+}
+
+// "Z", "+0200", "+02:00" or "-05" as seconds east of UTC
+fn parse_offset(offset: &str) -> Option<i64> {
+    if offset == "Z" {
+        return Some(0);
+    }
+    let sign = match offset.chars().next()? {
+        '+' => 1,
+        '-' => -1,
+        _ => return None,
+    };
+    let digits: String = offset[1..].chars().filter(|c| *c != ':').collect();
+    if !digits.chars().all(|c| c.is_ascii_digit()) || !matches!(digits.len(), 2 | 4) {
+        return None;
+    }
+    let hours: i64 = digits[..2].parse().ok()?;
+    let minutes: i64 = digits.get(2..).filter(|minutes| !minutes.is_empty()).map_or(Some(0), |minutes| minutes.parse().ok())?;
+    Some(sign * (hours * 3600 + minutes * 60))
+}
+
+// Days since 1970-01-01 of a proleptic Gregorian date (Howard Hinnant's algorithm)
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// Why the running system is out of date
+#[derive(Debug, Clone)]
+pub enum Reboot {
+    /// Debian's `/run/reboot-required`, with the packages that asked for it
+    Requested(Vec<String>),
+    /// A kernel other than the running one is installed and the running one is
+    /// gone (replaced in place, as on Arch) or older
+    Kernel(String),
+}
+
+/// Whether a reboot is pending; `running_kernel` is the release from `uname -r`
+pub fn pending_reboot(running_kernel: Option<&str>) -> Option<Reboot> {
+    if sysroot::exists("/run/reboot-required") || sysroot::exists("/var/run/reboot-required") {
+        let packages = sysroot::read_to_string("/run/reboot-required.pkgs")
+            .or_else(|_| sysroot::read_to_string("/var/run/reboot-required.pkgs"))
+            .unwrap_or_default();
+        // Every package that asked appends itself, so names repeat
+        let mut packages: Vec<String> = packages.lines().map(str::trim).filter(|name| !name.is_empty()).map(str::to_string).collect();
+        packages.sort();
+        packages.dedup();
+        return Some(Reboot::Requested(packages));
+    }
+
+    let running = running_kernel?;
+    let installed = installed_kernels();
+    let newest = installed
+        .iter()
+        // Don't compare against other flavours, e.g. linux-lts next to linux on Arch
+        .filter(|kernel| flavour(kernel) == flavour(running))
+        .max_by(|a, b| compare_versions(a, b))?;
+    let running_removed = !installed.iter().any(|kernel| kernel == running);
+    if running_removed || compare_versions(newest, running) == Ordering::Greater {
+        return Some(Reboot::Kernel(newest.clone()));
+    }
+    None
+}
+
+// Module trees of installed kernels. Removed kernels can leave a directory with
+// DKMS modules behind, so only trees depmod indexed count.
+fn installed_kernels() -> Vec<String> {
+    let Some(tree) = MODULE_TREES.iter().find(|tree| sysroot::exists(tree)) else {
+        return Vec::new();
+    };
+    sysroot::read_dir(tree)
+        .unwrap_or_default()
+        .iter()
+        .filter(|kernel| sysroot::exists(kernel.join("modules.dep")))
+        .filter_map(|kernel| Some(kernel.file_name()?.to_str()?.to_string()))
+        .collect()
+}
+
+// The letters of a kernel release: "generic" for 6.8.0-45-generic, "arch" for
+// 6.9.1-arch1-1, "lts" for 6.6.30-1-lts; enough to tell linux and linux-lts apart
+fn flavour(release: &str) -> String {
+    release.chars().filter(|c| c.is_ascii_alphabetic()).collect()
+}
+
+// Numeric runs compare as numbers, so 6.10.2 sorts after 6.9.12
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let runs = |version: &str| -> Vec<String> {
+        let mut runs: Vec<String> = Vec::new();
+        for c in version.chars() {
+            match runs.last_mut() {
+                Some(run) if run.chars().last().is_some_and(|last| last.is_ascii_digit() == c.is_ascii_digit()) => run.push(c),
+                _ => runs.push(c.to_string()),
+            }
+        }
+        runs
+    };
+    for (a, b) in runs(a).iter().zip(runs(b).iter()) {
+        let order = match (a.parse::<u64>(), b.parse::<u64>()) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            _ => a.cmp(b),
+        };
+        if order != Ordering::Equal {
+            return order;
+        }
+    }
+    runs(a).len().cmp(&runs(b).len())
+}
+
+const PENDING_FILE: &str = "pending_updates.json";
+const LOCK_FILE: &str = "pending_updates.lock";
+// Commands of a check are killed after this long, so a hung download can't keep
+// the lock forever; an older lock belongs to a check that died
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+const STALE_LOCK: Duration = Duration::from_secs(15 * 60);
+
+/// Updates one package manager has available
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Available {
+    pub manager: String,
+    pub count: usize,
+}
+
+/// Result of the last background check, as stored in the cache directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingUpdates {
+    /// Unix time in seconds when the check finished
+    pub checked: u64,
+    pub updates: Vec<Available>,
+}
+
+impl PendingUpdates {
+    pub fn load() -> Option<Self> {
+        let content = fs::read_to_string(cache::path(PENDING_FILE)?).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    pub fn age(&self) -> Duration {
+        let checked = UNIX_EPOCH + Duration::from_secs(self.checked);
+        checked.elapsed().unwrap_or_default()
+    }
+}
+
+/// Ask every package manager in use for its pending updates and store the result
+/// for the updates module. This downloads package lists, so it takes a while;
+/// the greeting starts it in the background or a timer runs `termgreet --refresh-updates`.
+pub fn refresh() -> Result<()> {
+    let Some(_lock) = Lock::acquire() else {
+        bail!("Another update check is already running");
+    };
+
+    let checks: [(&str, Counter); 3] = [
+        ("pacman", pacman_updates),
+        ("apt", apt_updates),
+        ("flatpak", flatpak_updates),
+    ];
+    let (updates, _) = commands::with_deadline(Instant::now() + CHECK_TIMEOUT, || {
+        checks
+            .iter()
+            .filter_map(|&(manager, check)| Some(Available { manager: manager.to_string(), count: check()? }))
+            .collect()
+    });
+
+    let pending = PendingUpdates {
+        checked: SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.as_secs()).unwrap_or(0),
+        updates,
+    };
+    cache::write(PENDING_FILE, &serde_json::to_string(&pending)?).context("Failed to save pending updates")?;
+    Ok(())
+}
+
+/// Start `termgreet --refresh-updates` detached from the greeting, unless a check
+/// is already running
+pub fn spawn_refresh() {
+    if Lock::is_held() {
+        return;
+    }
+    let Ok(executable) = env::current_exe() else {
+        return;
+    };
+    // Its own process group keeps Ctrl-C at the prompt from killing it
+    let _ = Command::new(executable)
+        .arg("--refresh-updates")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .process_group(0)
+        .spawn();
+}
+
+// Lock file in the cache directory, removed again when dropped
+struct Lock(PathBuf);
+
+impl Lock {
+    fn acquire() -> Option<Self> {
+        let path = cache::path(LOCK_FILE)?;
+        fs::create_dir_all(path.parent()?).ok()?;
+        if !Self::is_held() {
+            let _ = fs::remove_file(&path);
+        }
+        fs::OpenOptions::new().write(true).create_new(true).open(&path).ok()?;
+        Some(Self(path))
+    }
+
+    fn is_held() -> bool {
+        cache::path(LOCK_FILE)
+            .and_then(|path| fs::metadata(path).ok())
+            .and_then(|metadata| metadata.modified().ok())
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age < STALE_LOCK)
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+// Like checkupdates from pacman-contrib: sync a private copy of the databases so
+// the system's stay untouched and pacman -Su doesn't see a partial upgrade
+fn pacman_updates() -> Option<usize> {
+    let system = Path::new("/var/lib/pacman");
+    if !system.join("local").is_dir() {
+        return None;
+    }
+    let dbpath = cache::path("pacman")?;
+    fs::create_dir_all(dbpath.join("sync")).ok()?;
+    let local = dbpath.join("local");
+    if fs::symlink_metadata(&local).is_err() {
+        symlink(system.join("local"), &local).ok()?;
+    }
+
+    // Start from the system's databases so only what changed since is downloaded.
+    // pacman compares the modification times with the mirror's, so they are kept.
+    for database in fs::read_dir(system.join("sync")).ok()?.flatten() {
+        let copy = dbpath.join("sync").join(database.file_name());
+        let modified = |path: &Path| fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+        let Some(system_modified) = modified(&database.path()) else {
+            continue;
+        };
+        if modified(&copy).is_none_or(|copied| copied < system_modified) {
+            let _ = fs::copy(database.path(), &copy)
+                .and_then(|_| fs::File::options().write(true).open(&copy))
+                .and_then(|file| file.set_modified(system_modified));
+        }
+    }
+
+    let dbpath = dbpath.to_str()?;
+    // -Sy refuses to run without root, even on a private database path
+    commands::run("fakeroot", &["--", "pacman", "-Sy", "--dbpath", dbpath, "--logfile", "/dev/null"], &[])?;
+    // -Qu fails when there is nothing to upgrade
+    let upgrades = commands::run("pacman", &["-Qu", "--dbpath", dbpath], &[]).unwrap_or_default();
+    Some(upgrades.lines().count())
+}
+
+// From the package lists apt already has; refreshing them needs root
+fn apt_updates() -> Option<usize> {
+    if !Path::new("/var/lib/dpkg/status").exists() {
+        return None;
+    }
+    // "firefox-esr/stable-security 128.3.1esr-1~deb12u1 amd64 [upgradable from: 128.3.0esr-1~deb12u1]"
+    let upgradable = commands::run("apt", &["list", "--upgradable"], &[("LC_ALL", "C")])?;
+    Some(upgradable.lines().filter(|line| line.contains("[upgradable from:")).count())
+}
+
+fn flatpak_updates() -> Option<usize> {
+    let updates = commands::run("flatpak", &["remote-ls", "--updates", "--columns=application"], &[])?;
+    Some(updates.lines().filter(|line| !line.trim().is_empty()).count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unix(timestamp: &str) -> Option<u64> {
+        Some(parse_time(timestamp)?.duration_since(UNIX_EPOCH).ok()?.as_secs())
+    }
+
+    #[test]
+    fn applies_utc_offsets() {
+        // 2024-05-01 10:34:56 UTC
+        assert_eq!(unix("2024-05-01T10:34:56Z"), Some(1714559696));
+        assert_eq!(unix("2024-05-01T12:34:56+0200"), Some(1714559696));
+        assert_eq!(unix("2024-05-01T12:34:56+02:00"), Some(1714559696));
+        assert_eq!(unix("2024-05-01T05:34:56-05"), Some(1714559696));
+        assert_eq!(unix("2024-05-01T10:34Z"), Some(1714559640));
+    }
+
+    #[test]
+    fn reads_local_timestamps() {
+        // Local time depends on the time zone; it is only checked to parse
+        assert!(parse_time("2024-05-01  12:34:56").is_some());
+        assert!(parse_time("2024-05-01 12:34").is_some());
+        assert!(parse_time("2024-05-01").is_none());
+        assert!(parse_time("yesterday").is_none());
+    }
+
+    #[test]
+    fn counts_days_across_leap_years() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        assert_eq!(days_from_civil(2024, 3, 1) - days_from_civil(2024, 2, 28), 2);
+    }
+
+    #[test]
+    fn compares_numeric_runs_as_numbers() {
+        assert_eq!(compare_versions("6.10.2-arch1-1", "6.9.12-arch1-1"), Ordering::Greater);
+        assert_eq!(compare_versions("6.8.0-45-generic", "6.8.0-100-generic"), Ordering::Less);
+        assert_eq!(compare_versions("6.6.30-1-lts", "6.6.30-1-lts"), Ordering::Equal);
+        assert_eq!(compare_versions("6.6", "6.6.1"), Ordering::Less);
+    }
+
+    #[test]
+    fn tells_kernel_flavours_apart() {
+        assert_eq!(flavour("6.8.0-45-generic"), "generic");
+        assert_eq!(flavour("6.9.1-arch1-1"), "arch");
+        assert_eq!(flavour("6.6.30-1-lts"), "lts");
+    }
+}
@@ -0,0 +1,330 @@
+use std::fmt;
+use serde::de::{Deserialize, Deserializer, MapAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+/// Unit attached to a numeric value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Unit {
+    /// Plain count (packages, cores, days)
+    Count,
+    Bytes,
+    Percent,
+    Celsius,
+    /// Durations such as uptime
+    Seconds,
+    /// Fan speed
+    Rpm,
+    Watts,
+    /// Clock speeds, shown in MHz
+    Hertz,
+    /// Link speeds
+    BitsPerSecond,
+}
+
+/// A typed piece of system information.
+///
+/// Collectors report missing data as `Unavailable` instead of sentinel
+/// strings such as "Unknown" or "N/A", so display and machine-readable
+/// output can tell it apart from real values.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Text(String),
+    Number { value: f64, unit: Unit },
+    /// Several values of one kind, e.g. the addresses of an interface
+    List(Vec<Value>),
+    Unavailable { reason: String },
+}
+
+impl Value {
+    pub fn text(text: impl Into<String>) -> Self {
+        Value::Text(text.into())
+    }
+
+    pub fn number(value: impl Into<f64>, unit: Unit) -> Self {
+        Value::Number { value: value.into(), unit }
+    }
+
+    pub fn count(count: usize) -> Self {
+        Value::Number { value: count as f64, unit: Unit::Count }
+    }
+
+    pub fn bytes(bytes: u64) -> Self {
+        Value::Number { value: bytes as f64, unit: Unit::Bytes }
+    }
+
+    pub fn list<T: Into<Value>>(items: impl IntoIterator<Item = T>) -> Self {
+        Value::List(items.into_iter().map(Into::into).collect())
+    }
+
+    pub fn unavailable(reason: impl Into<String>) -> Self {
+        Value::Unavailable { reason: reason.into() }
+    }
+
+    /// Text if detected, otherwise `Unavailable` with the given reason
+    pub fn text_or(text: Option<String>, reason: &str) -> Self {
+        match text {
+            Some(text) => Value::Text(text),
+            None => Value::unavailable(reason),
+        }
+    }
+
+    pub fn is_available(&self) -> bool {
+        !matches!(self, Value::Unavailable { .. })
+    }
+
+    /// The number in its base unit; `None` for text and unavailable values
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number { value, .. } => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(text: &str) -> Self {
+        Value::Text(text.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(text: String) -> Self {
+        Value::Text(text)
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Text(text) => f.write_str(text),
+            Value::Number { value, unit } => match unit {
+                Unit::Count => write!(f, "{}", value.round() as i64),
+                Unit::Bytes => f.write_str(&format_bytes(*value)),
+                Unit::Percent => write!(f, "{:.0}%", value),
+                Unit::Celsius => write!(f, "{:.1}°C", value),
+                Unit::Seconds => f.write_str(&format_duration(*value as u64)),
+                Unit::Rpm => write!(f, "{:.0} RPM", value),
+                Unit::Hertz => write!(f, "{:.0} MHz", value / 1_000_000.0),
+                // "100 Mb/s", "1 Gb/s", "2.5 Gb/s"
+                Unit::BitsPerSecond if *value >= 1e9 => write!(f, "{} Gb/s", (value / 1e8).round() / 10.0),
+                Unit::BitsPerSecond => write!(f, "{:.0} Mb/s", value / 1e6),
+                Unit::Watts => {
+                    // "35 W" for whole watts, "18.4 W" otherwise
+                    let tenths = (value * 10.0).round() / 10.0;
+                    if tenths.fract() == 0.0 {
+                        write!(f, "{:.0} W", tenths)
+                    } else {
+                        write!(f, "{:.1} W", tenths)
+                    }
+                }
+            },
+            // Templates join lists with the module's separator; this is the fallback
+            Value::List(items) => {
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                Ok(())
+            }
+            // Missing values render as nothing; templates use {?field} to skip around them
+            Value::Unavailable { .. } => Ok(()),
+        }
+    }
+}
+
+// Machine-readable form: text as a string, numbers as `{ value, unit }`, lists as
+// arrays and missing data as `{ unavailable = reason }`; TOML has no null, so no Option here
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Text(text) => serializer.serialize_str(text),
+            Value::Number { value, unit } => {
+                let mut map = serializer.serialize_map(Some(2))?;
+                // Whole numbers (counts, bytes, seconds) stay integers
+                if value.fract() == 0.0 && value.abs() < 9.0e15 {
+                    map.serialize_entry("value", &(*value as i64))?;
+                } else {
+                    map.serialize_entry("value", value)?;
+                }
+                map.serialize_entry("unit", unit)?;
+                map.end()
+            }
+            Value::List(items) => items.serialize(serializer),
+            Value::Unavailable { reason } => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                map.serialize_entry("unavailable", reason)?;
+                map.end()
+            }
+        }
+    }
+}
+
+// Mirror of the serialized form, used to read values back from the cache
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum ValueRepr {
+    Text(String),
+    Number { value: f64, unit: Unit },
+    List(Vec<Value>),
+    Unavailable { unavailable: String },
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match ValueRepr::deserialize(deserializer)? {
+            ValueRepr::Text(text) => Value::Text(text),
+            ValueRepr::Number { value, unit } => Value::Number { value, unit },
+            ValueRepr::List(items) => Value::List(items),
+            ValueRepr::Unavailable { unavailable } => Value::Unavailable { reason: unavailable },
+        })
+    }
+}
+
+// Binary multiples, labelled GB/MB like the rest of the output
+fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit >= 3 {
+        format!("{:.1}{}", value, UNITS[unit])
+    } else {
+        format!("{:.0}{}", value, UNITS[unit])
+    }
+}
+
+fn format_duration(seconds: u64) -> String {
+    let days = seconds / 86400;
+    let hours = (seconds % 86400) / 3600;
+    let minutes = (seconds % 3600) / 60;
+
+    if days > 0 {
+        format!("{} days, {} hours, {} mins", days, hours, minutes)
+    } else if hours > 0 {
+        format!("{} hours, {} mins", hours, minutes)
+    } else {
+        format!("{} mins", minutes)
+    }
+}
+
+/// Named values of one module entry, in collection order.
+///
+/// Format strings in `[modules.format]` refer to these names; modules with a
+/// single unstructured value expose it as `value`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Fields(Vec<(String, Value)>);
+
+impl Fields {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Entry with a single `value` field
+    pub fn value(value: impl Into<Value>) -> Self {
+        Self::new().with("value", value)
+    }
+
+    pub fn with(mut self, name: &str, value: impl Into<Value>) -> Self {
+        self.set(name, value);
+        self
+    }
+
+    /// Add the field only if a value is present
+    pub fn with_opt(mut self, name: &str, value: Option<impl Into<Value>>) -> Self {
+        if let Some(value) = value {
+            self.set(name, value);
+        }
+        self
+    }
+
+    pub fn set(&mut self, name: &str, value: impl Into<Value>) {
+        let value = value.into();
+        match self.0.iter_mut().find(|(field, _)| field == name) {
+            Some(entry) => entry.1 = value,
+            None => self.0.push((name.to_string(), value)),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.0
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Present and not `Unavailable`
+    pub fn has(&self, name: &str) -> bool {
+        self.get(name).is_some_and(Value::is_available)
+    }
+
+    /// An entry with nothing available is not worth displaying
+    pub fn is_available(&self) -> bool {
+        self.0.iter().any(|(_, value)| value.is_available())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.0.iter().map(|(name, value)| (name.as_str(), value))
+    }
+}
+
+// Serialized as a map in collection order
+impl Serialize for Fields {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (name, value) in &self.0 {
+            map.serialize_entry(name, value)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Fields {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FieldsVisitor;
+
+        impl<'de> Visitor<'de> for FieldsVisitor {
+            type Value = Fields;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a map of field names to values")
+            }
+
+            // Keeps the order of the map, unlike collecting into a HashMap
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Fields, A::Error> {
+                let mut fields = Fields::new();
+                while let Some((name, value)) = map.next_entry::<String, Value>()? {
+                    fields.set(&name, value);
+                }
+                Ok(fields)
+            }
+        }
+
+        deserializer.deserialize_map(FieldsVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_json() {
+        let fields = Fields::new()
+            .with("dns", Value::list(["10.8.0.1", "1.1.1.1"]))
+            .with("used", Value::bytes(1024))
+            .with("gateway", Value::unavailable("no default route"));
+        let json = serde_json::to_string(&fields).unwrap();
+        assert_eq!(
+            json,
+            r#"{"dns":["10.8.0.1","1.1.1.1"],"used":{"value":1024,"unit":"bytes"},"gateway":{"unavailable":"no default route"}}"#
+        );
+        assert_eq!(serde_json::from_str::<Fields>(&json).unwrap(), fields);
+    }
+}
@@ -0,0 +1,118 @@
+// Runs the binary against the fixture tree in tests/fixtures/sysroot, with
+// external commands and HTTP lookups replayed from tests/fixtures/bundle.json
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use serde_json::Value;
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name)
+}
+
+fn collect() -> Value {
+    // Keep the user's own cache and config out of it
+    let home = Path::new(env!("CARGO_TARGET_TMPDIR")).join("home");
+    let output = Command::new(env!("CARGO_BIN_EXE_termgreet"))
+        .arg("--config")
+        .arg(fixture("config.toml"))
+        .arg("--sysroot")
+        .arg(fixture("sysroot"))
+        .arg("--replay")
+        .arg(fixture("bundle.json"))
+        .args(["--format", "json"])
+        .env("HOME", &home)
+        .env("XDG_CACHE_HOME", home.join(".cache"))
+        .env("XDG_CONFIG_HOME", home.join(".config"))
+        .output()
+        .expect("failed to run termgreet");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    serde_json::from_slice(&output.stdout).expect("invalid JSON output")
+}
+
+fn text(report: &Value, module: &str) -> String {
+    report[module]["text"].as_str().unwrap_or_default().to_string()
+}
+
+#[test]
+fn reads_release_files_from_the_sysroot() {
+    let report = collect();
+    assert_eq!(text(&report, "os"), "Fixture Linux 1.0");
+    assert_eq!(text(&report, "kernel"), "6.9.1-arch1-1");
+}
+
+#[test]
+fn names_gpus_from_pci_ids() {
+    let report = collect();
+    let gpus = report["gpu"]["entries"].as_array().unwrap();
+    // The boot GPU comes first
+    assert_eq!(gpus[0]["name"], "Intel Iris Xe Graphics");
+    assert_eq!(gpus[0]["primary"], "primary");
+    assert_eq!(gpus[1]["name"], "AMD Radeon RX 6800/6800 XT / 6900 XT");
+    assert_eq!(gpus[1]["driver"], "amdgpu");
+    // The starred level of pp_dpm_sclk
+    assert_eq!(gpus[1]["core_clock"]["value"], 2_105_000_000u64);
+    assert_eq!(text(&report, "gpu_driver"), "Intel i915 (open source) • AMDGPU (open source)");
+}
+
+#[test]
+fn finds_addresses_and_routes() {
+    let report = collect();
+    let network = &report["network"]["entries"][0];
+    assert_eq!(network["interface"], "enp5s0");
+    assert_eq!(network["ipv4"], serde_json::json!(["192.168.0.42"]));
+    assert_eq!(network["ipv6"], serde_json::json!(["2001:db8::42"]));
+    assert_eq!(network["gateway"], "192.168.0.1");
+
+    // resolv.conf points at the systemd-resolved stub, so its upstream servers are shown
+    let interfaces = report["interfaces"]["entries"].as_array().unwrap();
+    let dns = interfaces.last().unwrap();
+    assert_eq!(dns["dns"], serde_json::json!(["10.8.0.1", "1.1.1.1"]));
+    assert_eq!(dns["search"], serde_json::json!(["corp.example.com", "lan"]));
+}
+
+#[test]
+fn counts_packages_from_their_databases() {
+    let report = collect();
+    // pacman foreign packages come from the replayed `pacman -Qqm`
+    assert_eq!(
+        text(&report, "packages"),
+        "4 (dpkg: 2 manual, 2 auto), 3 (pacman: 1 explicit, 1 foreign, 1 orphan)"
+    );
+    assert_eq!(report["packages"]["entries"][1]["details"], serde_json::json!(["1 explicit", "1 foreign", "1 orphan"]));
+}
+
+#[test]
+fn reads_sensors_and_battery_draw() {
+    let report = collect();
+    let sensors = report["sensors"]["entries"].as_array().unwrap();
+    let junction = sensors.iter().find(|sensor| sensor["name"] == "amdgpu junction").unwrap();
+    assert_eq!(junction["temp"]["value"], 61);
+    assert_eq!(junction["crit"]["value"], 100);
+    // Unlabeled fans are named after the chip that reports them
+    assert_eq!(text(&report, "fans_power"), "CPU fan 850 RPM • GPU fan 1200 RPM • GPU 35 W • System 18.4 W");
+}
+
+#[test]
+fn replays_public_ip_lookups() {
+    let report = collect();
+    let lookup = &report["public_ip"]["entries"][0];
+    assert_eq!(lookup["address"], "203.0.113.7");
+    assert_eq!(lookup["asn"], "AS64500");
+    assert_eq!(lookup["isp"], "Example Networks");
+}
+
+#[test]
+fn leaves_disk_usage_of_a_sysroot_unmeasured() {
+    let report = collect();
+    // statvfs would report the host filesystem the fixtures are stored on
+    for module in ["disk", "dysk"] {
+        let entries = report[module]["entries"].as_array().unwrap();
+        assert!(!entries.is_empty());
+        for entry in entries {
+            assert_eq!(entry["total"]["unavailable"], "not measured for a sysroot");
+            assert_eq!(entry["used"]["unavailable"], "not measured for a sysroot");
+        }
+    }
+    assert_eq!(text(&report, "disk"), "/ [ext4]");
+    assert_eq!(report["dysk"]["entries"][1]["mount"], "/mnt/my disk");
+}